@@ -0,0 +1,27 @@
+#![allow(clippy::needless_return)]
+
+use aoc_2022::io_utils::Input;
+use aoc_2022::registry;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+/// Benchmarks parsing and both parts of every day that has its puzzle input in data/.
+fn days(c: &mut Criterion) {
+    for day in registry::days() {
+        let input = match Input::read_file(format!("data/day{}.txt", day.number)) {
+            Ok(input) => input,
+            Err(_) => continue,
+        };
+        let parsed = day.parse(input.text()).unwrap();
+
+        let mut group = c.benchmark_group(format!("day{}", day.number));
+        group.bench_function("parse", |b| {
+            b.iter(|| day.parse(black_box(input.text())).ok())
+        });
+        group.bench_function("part1", |b| b.iter(|| day.solve(1, black_box(&parsed))));
+        group.bench_function("part2", |b| b.iter(|| day.solve(2, black_box(&parsed))));
+        group.finish();
+    }
+}
+
+criterion_group!(benches, days);
+criterion_main!(benches);
@@ -0,0 +1,171 @@
+use crate::error::ParseError;
+use crate::registry::Day;
+use serde::{Serialize, Serializer};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+pub fn nanoseconds<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    return serializer.serialize_u64(duration.as_nanos() as u64);
+}
+
+/// The spread of the timings of one step over all iterations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Stats {
+    #[serde(rename = "min_ns", serialize_with = "nanoseconds")]
+    pub min: Duration,
+    #[serde(rename = "median_ns", serialize_with = "nanoseconds")]
+    pub median: Duration,
+    #[serde(rename = "max_ns", serialize_with = "nanoseconds")]
+    pub max: Duration,
+}
+
+impl Stats {
+    pub fn from_samples(mut samples: Vec<Duration>) -> Stats {
+        samples.sort();
+
+        return Stats {
+            min: samples.first().copied().unwrap_or_default(),
+            median: samples.get(samples.len() / 2).copied().unwrap_or_default(),
+            max: samples.last().copied().unwrap_or_default(),
+        };
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DayReport {
+    pub day: u8,
+    pub title: &'static str,
+    pub parse: Stats,
+    pub part1: Stats,
+    pub part2: Stats,
+}
+
+/// Runs `step` `warmup` times, then times `iterations` more runs of it.
+fn sample<F: FnMut()>(warmup: usize, iterations: usize, mut step: F) -> Stats {
+    for _ in 0..warmup {
+        step();
+    }
+
+    let mut samples = vec![];
+    for _ in 0..iterations {
+        let start = Instant::now();
+        step();
+        samples.push(start.elapsed());
+    }
+
+    return Stats::from_samples(samples);
+}
+
+/// Times parsing and both parts of `day` on `input`.
+pub fn bench(
+    day: &Day,
+    input: &str,
+    warmup: usize,
+    iterations: usize,
+) -> Result<DayReport, ParseError> {
+    let parsed = day.parse(input)?;
+
+    return Ok(DayReport {
+        day: day.number,
+        title: day.title,
+        parse: sample(warmup, iterations, || {
+            black_box(day.parse(input).ok());
+        }),
+        part1: sample(warmup, iterations, || {
+            black_box(day.solve(1, &parsed).ok());
+        }),
+        part2: sample(warmup, iterations, || {
+            black_box(day.solve(2, &parsed).ok());
+        }),
+    });
+}
+
+pub fn print_report(reports: &[DayReport]) {
+    println!(
+        "{:>3}  {:<24}  {:<5}  {:>12}  {:>12}  {:>12}",
+        "Day", "Title", "Step", "Min", "Median", "Max"
+    );
+
+    for report in reports {
+        for (step, stats) in [
+            ("parse", report.parse),
+            ("part1", report.part1),
+            ("part2", report.part2),
+        ] {
+            println!(
+                "{:>3}  {:<24}  {:<5}  {:>12}  {:>12}  {:>12}",
+                report.day,
+                report.title,
+                step,
+                format!("{:?}", stats.min),
+                format!("{:?}", stats.median),
+                format!("{:?}", stats.max)
+            );
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    warmup: usize,
+    iterations: usize,
+    days: &'a [DayReport],
+}
+
+pub fn to_json(reports: &[DayReport], warmup: usize, iterations: usize) -> String {
+    let report = JsonReport {
+        warmup,
+        iterations,
+        days: reports,
+    };
+
+    return serde_json::to_string_pretty(&report).expect("reports always serialize");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry;
+
+    #[test]
+    fn stats_from_samples_is_correct() {
+        let samples = vec![
+            Duration::from_micros(5),
+            Duration::from_micros(1),
+            Duration::from_micros(9),
+        ];
+
+        let stats = Stats::from_samples(samples);
+
+        assert_eq!(Duration::from_micros(1), stats.min);
+        assert_eq!(Duration::from_micros(5), stats.median);
+        assert_eq!(Duration::from_micros(9), stats.max);
+    }
+
+    #[test]
+    fn bench_reports_every_step() {
+        let day = registry::find(6).unwrap();
+
+        let report = bench(&day, "mjqjpqmgbljsphdztnvjfqwrcgsmlb", 1, 3).unwrap();
+
+        assert_eq!(6, report.day);
+        assert!(report.part1.min <= report.part1.median);
+        assert!(report.part1.median <= report.part1.max);
+    }
+
+    #[test]
+    fn to_json_uses_nanoseconds() {
+        let stats = Stats::from_samples(vec![Duration::from_nanos(42)]);
+        let report = DayReport {
+            day: 1,
+            title: "Calorie Counting",
+            parse: stats,
+            part1: stats,
+            part2: stats,
+        };
+
+        let json: serde_json::Value = serde_json::from_str(&to_json(&[report], 0, 1)).unwrap();
+
+        assert_eq!(42, json["days"][0]["part2"]["median_ns"]);
+    }
+}
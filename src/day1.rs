@@ -1,77 +1,101 @@
-use crate::io_utils::read_lines;
+use crate::error::ParseError;
+use crate::io_utils::Input;
+use crate::solution::{Answer, Solution};
 
-fn sums(lines: &Vec<String>) -> Vec<u32> {
-    let mut sums = vec![];
+pub struct Day1;
 
-    let mut i = 0;
-    while i < lines.len() {
-        let mut sum: u32 = 0;
+impl Solution for Day1 {
+    const DAY: u8 = 1;
+    const TITLE: &'static str = "Calorie Counting";
 
-        while i < lines.len() && !lines[i].is_empty() {
-            let number: u32 = lines[i].parse().unwrap();
-            sum += number;
+    type Input = Vec<Vec<u32>>;
 
-            i += 1;
-        }
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        return parse(input);
+    }
 
-        sums.push(sum);
-        i += 1;
+    fn part1(input: &Self::Input) -> Result<Answer, String> {
+        return Ok(part1(input).into());
     }
 
-    return sums;
+    fn part2(input: &Self::Input) -> Result<Answer, String> {
+        return Ok(part2(input).into());
+    }
 }
 
-fn part1(filename: &str) -> u32 {
-    let lines: Vec<String> = read_lines(filename)
-        .unwrap()
-        .into_iter()
-        .map(|x| x.unwrap())
-        .collect();
+pub fn parse(input: &str) -> Result<Vec<Vec<u32>>, ParseError> {
+    let input = Input::from_text(input);
+    let paragraphs = input.paragraphs();
 
-    let sums = sums(&lines);
+    if paragraphs.is_empty() {
+        return Err(ParseError::missing_field("calories", "", 1));
+    }
 
-    return sums.into_iter().max().unwrap();
+    return paragraphs
+        .iter()
+        .map(|paragraph| paragraph.parse_lines())
+        .collect();
 }
 
-fn part2(filename: &str) -> u32 {
-    let lines: Vec<String> = read_lines(filename)
-        .unwrap()
-        .into_iter()
-        .map(|x| x.unwrap())
-        .collect();
+fn sums(elves: &[Vec<u32>]) -> Vec<u32> {
+    return elves.iter().map(|calories| calories.iter().sum()).collect();
+}
 
-    let mut sums = sums(&lines);
-    sums.sort();
+pub fn part1(elves: &[Vec<u32>]) -> u32 {
+    let sums = sums(elves);
+
+    return sums.into_iter().max().unwrap_or(0);
+}
 
-    let largest_to_smallest: Vec<u32> = sums.into_iter().rev().collect();
+/// The calories carried by the top three elves, or by all of them when there are fewer.
+pub fn part2(elves: &[Vec<u32>]) -> u32 {
+    let mut sums = sums(elves);
+    sums.sort();
 
-    return largest_to_smallest[0] + largest_to_smallest[1] + largest_to_smallest[2];
+    return sums.into_iter().rev().take(3).sum();
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::io_utils::parse_file;
+
     #[test]
-    fn part1_example_is_correct() {
-        let result = part1("data/day1_example.txt");
-        assert_eq!(24_000, result);
+    fn parse_is_correct() {
+        let result = parse("1000\n2000\n\n4000\n").unwrap();
+
+        assert_eq!(vec![vec![1000, 2000], vec![4000]], result);
     }
 
     #[test]
-    fn part1_is_correct() {
-        let result = part1("data/day1.txt");
-        assert_eq!(66_616, result);
+    fn parse_rejects_invalid_calories() {
+        let error = parse("1000\nabc\n").err().unwrap();
+
+        assert_eq!(ParseError::bad_number("abc", 1).at_line(2), error);
     }
 
     #[test]
-    fn part2_example_is_correct() {
-        let result = part2("data/day1_example.txt");
-        assert_eq!(45_000, result);
+    fn parse_rejects_empty_input() {
+        assert_eq!(
+            Err(ParseError::missing_field("calories", "", 1)),
+            parse("\n")
+        );
+    }
+
+    #[test]
+    fn part2_with_few_elves_is_correct() {
+        assert_eq!(3000, part2(&parse("1000\n\n2000").unwrap()));
     }
 
     #[test]
-    fn part2_is_correct() {
-        let result = part2("data/day1.txt");
-        assert_eq!(199172, result);
+    fn part1_example_is_correct() {
+        let result = part1(&parse_file("data/day1_example.txt", parse));
+        assert_eq!(24_000, result);
+    }
+
+    #[test]
+    fn part2_example_is_correct() {
+        let result = part2(&parse_file("data/day1_example.txt", parse));
+        assert_eq!(45_000, result);
     }
 }
@@ -1,194 +1,133 @@
-use crate::io_utils::read_lines;
-use std::collections::HashSet;
-
-#[derive(Clone, Debug)]
-struct ComputerState {
-    x: i32,
-}
-
-struct Computer {
-    states: Vec<ComputerState>,
-}
-
-impl Computer {
-    fn signal_strength_at(&self, cycle: usize) -> i32 {
-        let x: i32 = self.states[cycle - 1].x;
-        return cycle as i32 * x;
+use crate::error::ParseError;
+use crate::solution::{Answer, Solution};
+
+mod asm;
+mod cpu;
+mod crt;
+mod debug;
+mod generate;
+mod trace;
+
+pub use asm::{assemble, disassemble, parse_instruction, stream_ops, OpStream, Program, Span};
+pub use cpu::{
+    Cpu, CycleHook, CycleLimitError, Instruction, Op, Operand, Register, Registers, SignalSampler,
+    StreamJumpError, DEFAULT_CYCLE_LIMIT,
+};
+pub use crt::{CRTScreen, FrameRecorder, GeometryWarning, ScreenConfig};
+pub use debug::{Breakpoint, DebugCommand, Debugger, Stop};
+pub use generate::{generate, generate_text, GenerateError};
+pub use trace::{TraceFormat, TraceRow, Tracer};
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+    const TITLE: &'static str = "Cathode-Ray Tube";
+
+    type Input = Program;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        return parse(input);
     }
-}
-
-trait Instruction {
-    fn execute(&self, computer: &mut Computer);
-}
 
-#[derive(Debug)]
-struct AddX {
-    increment: i32,
-}
-
-impl Instruction for AddX {
-    fn execute(&self, computer: &mut Computer) {
-        let x = computer.states.last().unwrap().x;
-
-        computer
-            .states
-            .push(computer.states.last().unwrap().clone());
-
-        computer.states.push(ComputerState {
-            x: x + self.increment,
-        });
+    fn part1(input: &Self::Input) -> Result<Answer, String> {
+        return part1(input).map(Answer::from).map_err(|e| e.to_string());
     }
-}
 
-#[derive(Debug)]
-struct Noop {}
-
-fn parse_instruction(s: &str) -> Box<dyn Instruction> {
-    let split: Vec<&str> = s.split(" ").collect();
-    return match split[0] {
-        "addx" => Box::new(AddX {
-            increment: split[1].parse().unwrap(),
-        }),
-        "noop" => Box::new(Noop {}),
-        _ => panic!("Cringe"),
-    };
-}
-
-impl Instruction for Noop {
-    fn execute(&self, computer: &mut Computer) {
-        computer
-            .states
-            .push(computer.states.last().unwrap().clone());
+    fn part2(input: &Self::Input) -> Result<Answer, String> {
+        return part2(input).map(Answer::from).map_err(|e| e.to_string());
     }
 }
 
-struct CRTScreen {
-    light: HashSet<(u32, u32)>,
-    height: u32,
-    width: u32,
+pub fn parse(input: &str) -> Result<Program, ParseError> {
+    return assemble(input);
 }
 
-impl CRTScreen {
-    fn to_string(&self) -> String {
-        let mut repr = String::from("");
-        for y in 0..self.height {
-            let mut line_repr = String::from("");
-            for x in 0..self.width {
-                if self.light.contains(&(x, y)) {
-                    line_repr += "#";
-                } else {
-                    line_repr += ".";
-                }
-            }
-
-            repr.push_str(&line_repr);
-            repr.push_str("\n");
-        }
-
-        return repr;
-    }
-
-    fn draw_pixel(&mut self, x: u32, y: u32) {
-        self.light.insert((x, y));
-    }
-
-    fn draw(&mut self, computer: &Computer) {
-        for cycle in 0..computer.states.len() {
-            let pixel_x = (cycle as u32) % self.width;
-            let pixel_y = ((cycle as u32) / self.width) % self.height;
+pub fn part1(program: &Program) -> Result<i64, CycleLimitError> {
+    let mut sampler = SignalSampler::every(20, 40).until(220);
 
-            let sprite_x = computer.states[cycle].x;
+    Cpu::new().run(&program.ops, &mut [&mut sampler])?;
 
-            if (pixel_x as i32 - sprite_x).abs() <= 1 {
-                self.draw_pixel(pixel_x, pixel_y);
-            }
-        }
-    }
+    return Ok(sampler.total());
 }
 
-fn part1(filename: &str) -> i32 {
-    let lines: Vec<String> = read_lines(filename).unwrap().map(|x| x.unwrap()).collect();
+/// The number of cycles `program` runs for, starting with X as configured.
+pub fn cycle_count(program: &Program, config: ScreenConfig) -> Result<usize, CycleLimitError> {
+    let mut cpu = config.cpu();
 
-    let instructions: Vec<Box<dyn Instruction>> =
-        lines.iter().map(|x| parse_instruction(x)).collect();
-
-    let mut computer: Computer = Computer {
-        states: vec![ComputerState { x: 1 }],
-    };
+    cpu.run(&program.ops, &mut [])?;
 
-    for instruction in instructions {
-        instruction.execute(&mut computer);
-    }
-
-    return computer.signal_strength_at(20)
-        + computer.signal_strength_at(60)
-        + computer.signal_strength_at(100)
-        + computer.signal_strength_at(140)
-        + computer.signal_strength_at(180)
-        + computer.signal_strength_at(220);
+    return Ok(cpu.cycle);
 }
 
-fn part2(filename: &str) -> String {
-    let lines: Vec<String> = read_lines(filename).unwrap().map(|x| x.unwrap()).collect();
+/// Runs `program` on a screen with the given geometry, starting with X as configured.
+pub fn draw(program: &Program, config: ScreenConfig) -> Result<CRTScreen, CycleLimitError> {
+    let mut screen = CRTScreen::with_config(config);
+    let mut cpu = config.cpu();
 
-    let instructions: Vec<Box<dyn Instruction>> =
-        lines.iter().map(|x| parse_instruction(x)).collect();
+    cpu.run(&program.ops, &mut [&mut screen])?;
 
-    let mut computer: Computer = Computer {
-        states: vec![ComputerState { x: 1 }],
-    };
+    return Ok(screen);
+}
 
-    for instruction in instructions {
-        instruction.execute(&mut computer);
-    }
+pub fn part2(program: &Program) -> Result<String, CycleLimitError> {
+    let screen = draw(program, ScreenConfig::default())?;
 
-    let mut screen = CRTScreen {
-        height: 6,
-        width: 40,
-        light: HashSet::new(),
+    // Not every program draws letters, the example draws a test pattern
+    return match screen.read_text() {
+        Ok(text) => Ok(text),
+        Err(error) => {
+            log::debug!("could not read the screen: {}", error);
+            Ok(screen.to_string())
+        }
     };
-
-    screen.draw(&computer);
-
-    return screen.to_string();
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::io_utils::parse_file;
+    use crate::ocr::OcrError;
 
     #[test]
     fn base_case_is_correct() {
-        let mut computer: Computer = Computer {
-            states: vec![ComputerState { x: 1 }],
-        };
+        let program = vec![parse_instruction("addx -15").unwrap()];
+        let mut cpu = Cpu::new();
 
-        let instruction = parse_instruction("addx -15");
+        assert!(cpu.step(&program, &mut []));
 
-        instruction.execute(&mut computer);
+        assert_eq!(2, cpu.cycle);
+        assert_eq!(-14, cpu.registers.x);
+        assert!(!cpu.step(&program, &mut []));
+    }
 
-        assert_eq!(3, computer.states.len());
-        assert_eq!(-14, computer.states.last().unwrap().x);
+    #[test]
+    fn parse_unknown_instruction_fails() {
+        let error = parse("noop\nsubx 3\n").err().unwrap();
+
+        assert_eq!(
+            ParseError::unexpected_token("subx", 1, "an instruction").at_line(2),
+            error
+        );
     }
 
     #[test]
-    fn part1_example_is_correct() {
-        let result = part1("./data/day10_example.txt");
+    fn parse_missing_argument_fails() {
+        let error = parse_instruction("addx").err().unwrap();
 
-        assert_eq!(13140, result);
+        assert_eq!(ParseError::missing_field("argument", "addx", 5), error);
     }
 
     #[test]
-    fn part1_is_correct() {
-        let result = part1("./data/day10.txt");
+    fn part1_example_is_correct() {
+        let result = part1(&parse_file("./data/day10_example.txt", parse)).unwrap();
 
-        assert_eq!(13520, result);
+        assert_eq!(13140, result);
     }
 
     #[test]
     fn part2_example_is_correct() {
-        let result = part2("./data/day10_example.txt");
-        println!("{:?}", result);
+        let result = part2(&parse_file("./data/day10_example.txt", parse)).unwrap();
 
         let expected = "##..##..##..##..##..##..##..##..##..##..\n###...###...###...###...###...###...###.\n####....####....####....####....####....\n#####.....#####.....#####.....#####.....\n######......######......######......####\n#######.......#######.......#######.....\n";
 
@@ -196,10 +135,71 @@ mod tests {
     }
 
     #[test]
-    fn part2_is_correct() {
-        let result = part2("./data/day10.txt");
-        let expected = "###...##..###..#..#.###..####..##..###..\n#..#.#..#.#..#.#..#.#..#.#....#..#.#..#.\n#..#.#....#..#.####.###..###..#..#.###..\n###..#.##.###..#..#.#..#.#....####.#..#.\n#....#..#.#....#..#.#..#.#....#..#.#..#.\n#.....###.#....#..#.###..####.#..#.###..\n";
+    fn cycle_count_is_correct() {
+        assert_eq!(
+            Ok(240),
+            cycle_count(
+                &parse_file("./data/day10_example.txt", parse),
+                ScreenConfig::default()
+            )
+        );
+    }
 
-        assert_eq!(expected, result);
+    #[test]
+    fn cycle_count_starts_at_configured_x() {
+        let program = assemble(
+            "jz x end
+addx 1
+end: noop",
+        )
+        .unwrap();
+        let config = ScreenConfig {
+            start_x: 0,
+            ..ScreenConfig::default()
+        };
+
+        assert_eq!(Ok(3), cycle_count(&program, config));
+        assert_eq!(Ok(5), cycle_count(&program, ScreenConfig::default()));
+    }
+
+    #[test]
+    fn draw_uses_config() {
+        let program = assemble("addx 1\naddx -1\nnoop").unwrap();
+        let config = ScreenConfig {
+            width: 2,
+            height: 2,
+            sprite_width: 1,
+            wrap: false,
+            start_x: 0,
+        };
+
+        assert_eq!("#.\n.#\n", draw(&program, config).unwrap().to_string());
+    }
+
+    #[test]
+    fn endless_program_reports_error() {
+        let program = assemble("start: addx 1\njmp start").unwrap();
+
+        assert_eq!(
+            Err(format!(
+                "the program was still running after {} cycles, at instruction 0, does it loop forever?",
+                DEFAULT_CYCLE_LIMIT
+            )),
+            Day10::part1(&program)
+        );
+    }
+
+    #[test]
+    fn read_text_reports_unknown_glyph() {
+        let screen = draw(
+            &parse_file("./data/day10_example.txt", parse),
+            ScreenConfig::default(),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            screen.read_text(),
+            Err(OcrError::UnknownGlyph { column: 0, .. })
+        ));
     }
 }
@@ -0,0 +1,450 @@
+use super::cpu::{Op, Operand, Register};
+use crate::error::{column_of, parse_number, ParseError};
+use crate::io_utils::Input;
+use std::fmt;
+
+/// Where an op was written in the source. Lines and columns are 1-based.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub len: usize,
+}
+
+/// An assembled program.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Program {
+    pub ops: Vec<Op>,
+    /// The source of every op, by index. Empty for programs that were not assembled.
+    pub spans: Vec<Span>,
+    /// Every label with the index of the op it points at, in source order. A label at the end
+    /// of the program points one past the last op.
+    pub labels: Vec<(String, usize)>,
+}
+
+impl Program {
+    pub fn from_ops(ops: Vec<Op>) -> Program {
+        return Program {
+            ops,
+            ..Program::default()
+        };
+    }
+
+    /// The index of the op `name` points at.
+    pub fn label(&self, name: &str) -> Option<usize> {
+        return self
+            .labels
+            .iter()
+            .find(|(label, _)| label == name)
+            .map(|(_, index)| *index);
+    }
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            Register::X => write!(f, "x"),
+            Register::A => write!(f, "a"),
+            Register::B => write!(f, "b"),
+            Register::C => write!(f, "c"),
+        };
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            Operand::Register(register) => write!(f, "{}", register),
+            Operand::Value(value) => write!(f, "{}", value),
+        };
+    }
+}
+
+/// Writes `op`, with jump targets written by `target`.
+fn write_op<F: Fn(usize) -> String>(f: &mut fmt::Formatter, op: &Op, target: F) -> fmt::Result {
+    return match op {
+        Op::AddX(increment) => write!(f, "addx {}", increment),
+        Op::Noop => write!(f, "noop"),
+        Op::Mov(register, operand) => write!(f, "mov {} {}", register, operand),
+        Op::Mul(register, operand) => write!(f, "mul {} {}", register, operand),
+        Op::Jmp(index) => write!(f, "jmp {}", target(*index)),
+        Op::Jz(register, index) => write!(f, "jz {} {}", register, target(*index)),
+        Op::Jnz(register, index) => write!(f, "jnz {} {}", register, target(*index)),
+        Op::Halt => write!(f, "halt"),
+    };
+}
+
+/// Jump targets are written as instruction numbers.
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write_op(f, self, |index| index.to_string());
+    }
+}
+
+/// Disassembles the program into canonical text: one op per line, labels on their own line
+/// before the op they point at, jump targets written as the first label that points at them,
+/// and no comments.
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let target = |index: usize| match self.labels.iter().find(|(_, x)| *x == index) {
+            Some((label, _)) => label.clone(),
+            None => index.to_string(),
+        };
+
+        for index in 0..=self.ops.len() {
+            for (label, _) in self.labels.iter().filter(|(_, x)| *x == index) {
+                writeln!(f, "{}:", label)?;
+            }
+
+            if let Some(op) = self.ops.get(index) {
+                write_op(f, op, target)?;
+                writeln!(f)?;
+            }
+        }
+
+        return Ok(());
+    }
+}
+
+/// Turns a program back into canonical text, see the `Display` implementation of `Program`.
+pub fn disassemble(program: &Program) -> String {
+    return program.to_string();
+}
+
+fn parse_register(s: &str, token: &str) -> Result<Register, ParseError> {
+    return match token {
+        "x" => Ok(Register::X),
+        "a" => Ok(Register::A),
+        "b" => Ok(Register::B),
+        "c" => Ok(Register::C),
+        _ => Err(ParseError::unexpected_token(
+            token,
+            column_of(s, token),
+            "a register: x, a, b or c",
+        )),
+    };
+}
+
+fn parse_operand(s: &str, token: &str) -> Result<Operand, ParseError> {
+    if token.starts_with(|x: char| x.is_ascii_alphabetic()) {
+        return Ok(Operand::Register(parse_register(s, token)?));
+    }
+
+    return Ok(Operand::Value(parse_number(s, token)?));
+}
+
+/// Reads a jump target: the number of an instruction, or one of `labels`.
+fn parse_target(s: &str, token: &str, labels: &[(String, usize)]) -> Result<usize, ParseError> {
+    if !is_label(token) {
+        return parse_number(s, token);
+    }
+
+    return match labels.iter().find(|(label, _)| label == token) {
+        Some((_, index)) => Ok(*index),
+        None => Err(ParseError::unexpected_token(
+            token,
+            column_of(s, token),
+            "a label that is defined",
+        )),
+    };
+}
+
+/// Parses an instruction whose jump targets may name `labels`.
+fn parse_op(s: &str, labels: &[(String, usize)]) -> Result<Op, ParseError> {
+    let split: Vec<&str> = s.split_whitespace().collect();
+
+    let mnemonic = match split.first() {
+        Some(mnemonic) => *mnemonic,
+        None => return Err(ParseError::missing_field("instruction", s, 1)),
+    };
+
+    let arguments = match mnemonic {
+        "noop" | "halt" => 0,
+        "addx" | "jmp" => 1,
+        "mov" | "mul" | "jz" | "jnz" => 2,
+        _ => {
+            return Err(ParseError::unexpected_token(
+                mnemonic,
+                column_of(s, mnemonic),
+                "an instruction",
+            ))
+        }
+    };
+
+    if split.len() <= arguments {
+        return Err(ParseError::missing_field("argument", s, s.len() + 1));
+    }
+
+    if let Some(token) = split.get(arguments + 1) {
+        return Err(ParseError::unexpected_token(
+            token,
+            column_of(s, token),
+            "end of line",
+        ));
+    }
+
+    return match mnemonic {
+        "addx" => Ok(Op::AddX(parse_number(s, split[1])?)),
+        "mov" => Ok(Op::Mov(
+            parse_register(s, split[1])?,
+            parse_operand(s, split[2])?,
+        )),
+        "mul" => Ok(Op::Mul(
+            parse_register(s, split[1])?,
+            parse_operand(s, split[2])?,
+        )),
+        "jmp" => Ok(Op::Jmp(parse_target(s, split[1], labels)?)),
+        "jz" => Ok(Op::Jz(
+            parse_register(s, split[1])?,
+            parse_target(s, split[2], labels)?,
+        )),
+        "jnz" => Ok(Op::Jnz(
+            parse_register(s, split[1])?,
+            parse_target(s, split[2], labels)?,
+        )),
+        "halt" => Ok(Op::Halt),
+        "noop" => Ok(Op::Noop),
+        _ => Err(ParseError::unexpected_token(
+            mnemonic,
+            column_of(s, mnemonic),
+            "an instruction",
+        )),
+    };
+}
+
+/// Parses a single instruction like `addx -3` or `jz a 4`, without labels or comments. Jump
+/// targets are instruction numbers.
+pub fn parse_instruction(s: &str) -> Result<Op, ParseError> {
+    return parse_op(s, &[]);
+}
+
+/// Parses instructions one line at a time as they are iterated, see `stream_ops`.
+pub struct OpStream<I> {
+    lines: I,
+    line: usize,
+}
+
+impl<S: AsRef<str>, I: Iterator<Item = S>> Iterator for OpStream<I> {
+    type Item = Result<Op, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for line in self.lines.by_ref() {
+            let line = line.as_ref();
+            self.line += 1;
+
+            let code = match line.find(';') {
+                Some(comment) => &line[..comment],
+                None => line,
+            };
+            let statement = code.trim();
+
+            if statement.is_empty() {
+                continue;
+            }
+
+            let column = column_of(line, statement);
+
+            return Some(
+                parse_instruction(statement)
+                    .map_err(|e| e.offset_columns(column - 1).at_line(self.line)),
+            );
+        }
+
+        return None;
+    }
+}
+
+/// Parses the instructions of `lines` lazily, so that a program can run while it is being
+/// read. Comments and blank lines are skipped like in `assemble`, but labels are not
+/// supported and jump targets are instruction numbers.
+pub fn stream_ops<S: AsRef<str>, I: IntoIterator<Item = S>>(lines: I) -> OpStream<I::IntoIter> {
+    return OpStream {
+        lines: lines.into_iter(),
+        line: 0,
+    };
+}
+
+fn is_label(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    return chars
+        .next()
+        .is_some_and(|x| x.is_ascii_alphabetic() || x == '_')
+        && chars.all(|x| x.is_ascii_alphanumeric() || x == '_');
+}
+
+/// Assembles `source`: one instruction per line, optionally preceded by a `label:`. Everything
+/// after a `;` is a comment, and blank lines are skipped. Jumps may target labels defined
+/// anywhere in the program.
+pub fn assemble(source: &str) -> Result<Program, ParseError> {
+    let mut program = Program::default();
+    // The labels come first, so the statements are parsed once every label is known
+    let mut statements = vec![];
+    let input = Input::from_text(source);
+
+    for (i, line) in input.lines().enumerate() {
+        let code = match line.find(';') {
+            Some(comment) => &line[..comment],
+            None => line,
+        };
+        let mut statement = code.trim();
+
+        if let Some(colon) = statement.find(':') {
+            let label = statement[..colon].trim();
+            let column = column_of(line, statement);
+
+            if !is_label(label) {
+                return Err(
+                    ParseError::unexpected_token(label, column, "a label name").at_line(i + 1)
+                );
+            }
+
+            if program.label(label).is_some() {
+                return Err(ParseError::unexpected_token(
+                    label,
+                    column,
+                    "a label that is not defined yet",
+                )
+                .at_line(i + 1));
+            }
+
+            program.labels.push((String::from(label), statements.len()));
+            statement = statement[colon + 1..].trim();
+        }
+
+        if !statement.is_empty() {
+            statements.push((i + 1, line, statement));
+        }
+    }
+
+    for (line_number, line, statement) in statements {
+        let column = column_of(line, statement);
+        let op = parse_op(statement, &program.labels)
+            .map_err(|e| e.offset_columns(column - 1).at_line(line_number))?;
+
+        program.ops.push(op);
+        program.spans.push(Span {
+            line: line_number,
+            column,
+            len: statement.len(),
+        });
+    }
+
+    return Ok(program);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assemble_is_correct() {
+        let program =
+            assemble("; a comment\nstart: noop\n\n  addx -3 ; move left\nend:\n").unwrap();
+
+        assert_eq!(vec![Op::Noop, Op::AddX(-3)], program.ops);
+        assert_eq!(
+            Span {
+                line: 4,
+                column: 3,
+                len: 7
+            },
+            program.spans[1]
+        );
+        assert_eq!(Some(0), program.label("start"));
+        assert_eq!(Some(2), program.label("end"));
+    }
+
+    #[test]
+    fn assemble_reports_error_position() {
+        let error = assemble("noop\nloop: addx 1 2").err().unwrap();
+
+        assert_eq!(
+            ParseError::unexpected_token("2", 14, "end of line").at_line(2),
+            error
+        );
+    }
+
+    #[test]
+    fn assemble_rejects_duplicate_label() {
+        let error = assemble("a:\nnoop\na: noop").err().unwrap();
+
+        assert_eq!(
+            ParseError::unexpected_token("a", 1, "a label that is not defined yet").at_line(3),
+            error
+        );
+    }
+
+    #[test]
+    fn assemble_resolves_forward_jumps() {
+        let program =
+            assemble("mov a 2\nloop: jz a end\nmul x -3\naddx 1\nmov x a\njmp loop\nend: halt")
+                .unwrap();
+
+        assert_eq!(
+            vec![
+                Op::Mov(Register::A, Operand::Value(2)),
+                Op::Jz(Register::A, 6),
+                Op::Mul(Register::X, Operand::Value(-3)),
+                Op::AddX(1),
+                Op::Mov(Register::X, Operand::Register(Register::A)),
+                Op::Jmp(1),
+                Op::Halt,
+            ],
+            program.ops
+        );
+        assert_eq!("jz a 6", program.ops[1].to_string());
+    }
+
+    #[test]
+    fn assemble_rejects_undefined_label() {
+        let error = assemble("noop\njnz x nowhere").err().unwrap();
+
+        assert_eq!(
+            ParseError::unexpected_token("nowhere", 7, "a label that is defined").at_line(2),
+            error
+        );
+    }
+
+    #[test]
+    fn parse_instruction_rejects_bad_register() {
+        assert_eq!(
+            Err(ParseError::unexpected_token(
+                "y",
+                5,
+                "a register: x, a, b or c"
+            )),
+            parse_instruction("mov y 3")
+        );
+    }
+
+    #[test]
+    fn stream_ops_is_lazy() {
+        let mut ops = stream_ops(["noop ; wait", "", "  addx -3", "addx", "oops"]);
+
+        assert_eq!(Some(Ok(Op::Noop)), ops.next());
+        assert_eq!(Some(Ok(Op::AddX(-3))), ops.next());
+        assert_eq!(
+            Some(Err(
+                ParseError::missing_field("argument", "addx", 5).at_line(4)
+            )),
+            ops.next()
+        );
+        assert!(ops.next().unwrap().is_err());
+        assert_eq!(None, ops.next());
+    }
+
+    #[test]
+    fn disassemble_round_trips() {
+        let source =
+            "start:\naddx 15\nmov b x\nmiddle:\nmul b 2\njnz b end\njmp start\naddx -11\nend:\n";
+
+        let program = assemble(source).unwrap();
+        let text = disassemble(&program);
+        let reassembled = assemble(&text).unwrap();
+
+        assert_eq!(source, text);
+        assert_eq!(program.ops, reassembled.ops);
+        assert_eq!(program.labels, reassembled.labels);
+    }
+}
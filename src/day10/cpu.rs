@@ -0,0 +1,643 @@
+use std::fmt;
+
+/// How many cycles `Cpu::run` allows by default before it gives up on a program that jumps.
+pub const DEFAULT_CYCLE_LIMIT: usize = 1_000_000;
+
+/// The registers of the CPU. X holds the horizontal position of the middle of the sprite, A,
+/// B and C are general purpose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Registers {
+    pub x: i32,
+    pub a: i32,
+    pub b: i32,
+    pub c: i32,
+}
+
+impl Default for Registers {
+    fn default() -> Self {
+        return Registers {
+            x: 1,
+            a: 0,
+            b: 0,
+            c: 0,
+        };
+    }
+}
+
+impl Registers {
+    pub fn get(&self, register: Register) -> i32 {
+        return match register {
+            Register::X => self.x,
+            Register::A => self.a,
+            Register::B => self.b,
+            Register::C => self.c,
+        };
+    }
+
+    pub fn set(&mut self, register: Register, value: i32) {
+        match register {
+            Register::X => self.x = value,
+            Register::A => self.a = value,
+            Register::B => self.b = value,
+            Register::C => self.c = value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Register {
+    X,
+    A,
+    B,
+    C,
+}
+
+/// The value an instruction reads: a register or a number written in the instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    Register(Register),
+    Value(i32),
+}
+
+impl Operand {
+    pub fn value(&self, registers: &Registers) -> i32 {
+        return match self {
+            Operand::Register(register) => registers.get(*register),
+            Operand::Value(value) => *value,
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Cpu {
+    pub registers: Registers,
+    /// The index of the instruction in progress, or of the next one to fetch.
+    pub pc: usize,
+    /// The cycle in progress while hooks run, starting at 1. Between cycles it is the last
+    /// completed cycle.
+    pub cycle: usize,
+    /// The cycles left before the instruction at `pc` completes, 0 if it has not been fetched.
+    pub remaining: usize,
+    /// Set by `halt`, after which no instruction runs.
+    pub halted: bool,
+    /// The number of cycles after which `run` stops a program that has not ended. Programs
+    /// without jumps always end, so they have no limit.
+    pub cycle_limit: usize,
+}
+
+impl Default for Cpu {
+    fn default() -> Self {
+        return Cpu {
+            registers: Registers::default(),
+            pc: 0,
+            cycle: 0,
+            remaining: 0,
+            halted: false,
+            cycle_limit: DEFAULT_CYCLE_LIMIT,
+        };
+    }
+}
+
+/// A program was still running when it reached the cycle limit, most likely because it loops
+/// forever.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CycleLimitError {
+    pub limit: usize,
+    /// The instruction the program was at.
+    pub pc: usize,
+}
+
+impl fmt::Display for CycleLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(
+            f,
+            "the program was still running after {} cycles, at instruction {}, does it loop forever?",
+            self.limit, self.pc
+        );
+    }
+}
+
+/// A streamed program reached a jump, which cannot be followed without keeping the program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamJumpError {
+    /// The position of the jump in the stream.
+    pub pc: usize,
+}
+
+impl fmt::Display for StreamJumpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(
+            f,
+            "instruction {} jumps, which a streamed program cannot do",
+            self.pc
+        );
+    }
+}
+
+pub trait Instruction {
+    /// The number of cycles the instruction takes to complete, at least 1.
+    fn cycles(&self) -> usize;
+
+    /// Whether the instruction may change the program counter.
+    fn jumps(&self) -> bool {
+        return false;
+    }
+
+    /// Applies the effect of the instruction at the end of its last cycle. The program counter
+    /// already points at the next instruction.
+    fn execute(&self, cpu: &mut Cpu);
+}
+
+/// Watches the CPU during every cycle, before the instruction in progress has taken effect.
+pub trait CycleHook {
+    fn on_cycle(&mut self, cpu: &Cpu);
+
+    /// Called at the end of every cycle, once an instruction completing in it has taken effect.
+    fn after_cycle(&mut self, _cpu: &Cpu) {}
+}
+
+impl<F: FnMut(&Cpu)> CycleHook for F {
+    fn on_cycle(&mut self, cpu: &Cpu) {
+        self(cpu);
+    }
+}
+
+/// The instruction set of the CPU. Jump targets are indices of instructions, jumping past the
+/// last one ends the program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    /// Adds the argument to X, in 2 cycles. Like `mul`, it wraps around on overflow.
+    AddX(i32),
+    /// Does nothing for a cycle.
+    Noop,
+    /// Copies the operand into the register, in 1 cycle.
+    Mov(Register, Operand),
+    /// Multiplies the register by the operand, in 3 cycles.
+    Mul(Register, Operand),
+    /// Jumps to the target, in 1 cycle.
+    Jmp(usize),
+    /// Jumps to the target if the register is zero, in 2 cycles.
+    Jz(Register, usize),
+    /// Jumps to the target if the register is not zero, in 2 cycles.
+    Jnz(Register, usize),
+    /// Ends the program, in 1 cycle.
+    Halt,
+}
+
+impl Instruction for Op {
+    fn cycles(&self) -> usize {
+        return match self {
+            Op::AddX(_) => 2,
+            Op::Noop => 1,
+            Op::Mov(_, _) => 1,
+            Op::Mul(_, _) => 3,
+            Op::Jmp(_) => 1,
+            Op::Jz(_, _) | Op::Jnz(_, _) => 2,
+            Op::Halt => 1,
+        };
+    }
+
+    fn jumps(&self) -> bool {
+        return matches!(self, Op::Jmp(_) | Op::Jz(_, _) | Op::Jnz(_, _));
+    }
+
+    fn execute(&self, cpu: &mut Cpu) {
+        match self {
+            Op::AddX(increment) => cpu.registers.x = cpu.registers.x.wrapping_add(*increment),
+            Op::Noop => {}
+            Op::Mov(register, operand) => {
+                let value = operand.value(&cpu.registers);
+                cpu.registers.set(*register, value);
+            }
+            Op::Mul(register, operand) => {
+                let value = cpu
+                    .registers
+                    .get(*register)
+                    .wrapping_mul(operand.value(&cpu.registers));
+                cpu.registers.set(*register, value);
+            }
+            Op::Jmp(target) => cpu.pc = *target,
+            Op::Jz(register, target) => {
+                if cpu.registers.get(*register) == 0 {
+                    cpu.pc = *target;
+                }
+            }
+            Op::Jnz(register, target) => {
+                if cpu.registers.get(*register) != 0 {
+                    cpu.pc = *target;
+                }
+            }
+            Op::Halt => cpu.halted = true,
+        }
+    }
+}
+
+impl Cpu {
+    pub fn new() -> Cpu {
+        return Cpu::default();
+    }
+
+    pub fn with_registers(registers: Registers) -> Cpu {
+        return Cpu {
+            registers,
+            ..Cpu::default()
+        };
+    }
+
+    /// The cycle number multiplied by X, meant to be sampled during a cycle.
+    pub fn signal_strength(&self) -> i64 {
+        return self.cycle as i64 * self.registers.x as i64;
+    }
+
+    /// Runs a single cycle of `instruction`, starting it first if none is in progress, and
+    /// executes it if this was its last cycle.
+    fn cycle_of<I: Instruction>(&mut self, instruction: &I, hooks: &mut [&mut dyn CycleHook]) {
+        if self.remaining == 0 {
+            self.remaining = instruction.cycles().max(1);
+        }
+
+        self.cycle += 1;
+
+        for hook in hooks.iter_mut() {
+            hook.on_cycle(self);
+        }
+
+        self.remaining -= 1;
+
+        if self.remaining == 0 {
+            self.pc += 1;
+            instruction.execute(self);
+        }
+
+        for hook in hooks.iter_mut() {
+            hook.after_cycle(self);
+        }
+    }
+
+    /// Runs a single cycle, fetching the instruction at `pc` first if none is in progress, and
+    /// executes the instruction if this was its last cycle. Returns false when there is no
+    /// instruction left or the program has halted.
+    pub fn tick<I: Instruction>(
+        &mut self,
+        program: &[I],
+        hooks: &mut [&mut dyn CycleHook],
+    ) -> bool {
+        if self.halted {
+            return false;
+        }
+
+        return match program.get(self.pc) {
+            Some(instruction) => {
+                self.cycle_of(instruction, hooks);
+                true
+            }
+            None => false,
+        };
+    }
+
+    /// Runs cycles until the instruction in progress, or the next one, has been executed.
+    /// Returns false when there is no instruction left.
+    pub fn step<I: Instruction>(
+        &mut self,
+        program: &[I],
+        hooks: &mut [&mut dyn CycleHook],
+    ) -> bool {
+        if !self.tick(program, hooks) {
+            return false;
+        }
+
+        while self.remaining > 0 {
+            self.tick(program, hooks);
+        }
+
+        return true;
+    }
+
+    /// Whether the program has ended, by halting or by running past its last instruction.
+    pub fn has_ended<I: Instruction>(&self, program: &[I]) -> bool {
+        return self.halted || self.pc >= program.len();
+    }
+
+    /// Whether running `program` may never end, because it has a jump.
+    pub fn may_loop<I: Instruction>(program: &[I]) -> bool {
+        return program.iter().any(|x| x.jumps());
+    }
+
+    /// Runs `program` until it ends, or fails once a program that may loop has run for
+    /// `cycle_limit` cycles.
+    pub fn run<I: Instruction>(
+        &mut self,
+        program: &[I],
+        hooks: &mut [&mut dyn CycleHook],
+    ) -> Result<(), CycleLimitError> {
+        let limited = Cpu::may_loop(program);
+
+        while !self.has_ended(program) {
+            if limited && self.cycle >= self.cycle_limit {
+                return Err(CycleLimitError {
+                    limit: self.cycle_limit,
+                    pc: self.pc,
+                });
+            }
+
+            self.tick(program, hooks);
+        }
+
+        return Ok(());
+    }
+
+    /// Runs instructions as they come, one after the other, without keeping them. Memory use
+    /// does not grow with the length of the program, so it suits programs that are generated
+    /// or read on the fly. Each instruction only executes once, so the stream fails at the
+    /// first jump, and `halt` stops it.
+    pub fn run_stream<I: Instruction, T: IntoIterator<Item = I>>(
+        &mut self,
+        instructions: T,
+        hooks: &mut [&mut dyn CycleHook],
+    ) -> Result<(), StreamJumpError> {
+        for instruction in instructions {
+            if self.halted {
+                break;
+            }
+
+            if instruction.jumps() {
+                return Err(StreamJumpError { pc: self.pc });
+            }
+
+            self.cycle_of(&instruction, hooks);
+
+            while self.remaining > 0 {
+                self.cycle_of(&instruction, hooks);
+            }
+        }
+
+        return Ok(());
+    }
+}
+
+/// The cycles during which a `SignalSampler` samples.
+#[derive(Debug, Clone)]
+enum Schedule {
+    Cycles(Vec<usize>),
+    Every {
+        first: usize,
+        period: usize,
+        last: Option<usize>,
+    },
+}
+
+impl Schedule {
+    fn contains(&self, cycle: usize) -> bool {
+        return match self {
+            Schedule::Cycles(cycles) => cycles.contains(&cycle),
+            Schedule::Every {
+                first,
+                period,
+                last,
+            } => {
+                cycle >= *first
+                    && (cycle - first).is_multiple_of(*period)
+                    && last.is_none_or(|last| cycle <= last)
+            }
+        };
+    }
+}
+
+/// Adds up the signal strength during a set of cycles, without keeping anything else.
+#[derive(Debug, Clone)]
+pub struct SignalSampler {
+    schedule: Schedule,
+    samples: usize,
+    total: i64,
+}
+
+impl SignalSampler {
+    /// Samples during each of `cycles`.
+    pub fn new(cycles: &[usize]) -> SignalSampler {
+        return SignalSampler::with_schedule(Schedule::Cycles(cycles.to_vec()));
+    }
+
+    /// Samples during `first` and then every `period` cycles for as long as the program runs.
+    pub fn every(first: usize, period: usize) -> SignalSampler {
+        return SignalSampler::with_schedule(Schedule::Every {
+            first,
+            period: period.max(1),
+            last: None,
+        });
+    }
+
+    /// Stops sampling after cycle `last`.
+    pub fn until(self, last: usize) -> SignalSampler {
+        return match self.schedule {
+            Schedule::Every { first, period, .. } => {
+                SignalSampler::with_schedule(Schedule::Every {
+                    first,
+                    period,
+                    last: Some(last),
+                })
+            }
+            Schedule::Cycles(cycles) => SignalSampler::with_schedule(Schedule::Cycles(
+                cycles.into_iter().filter(|x| *x <= last).collect(),
+            )),
+        };
+    }
+
+    fn with_schedule(schedule: Schedule) -> SignalSampler {
+        return SignalSampler {
+            schedule,
+            samples: 0,
+            total: 0,
+        };
+    }
+
+    /// The number of cycles sampled so far.
+    pub fn samples(&self) -> usize {
+        return self.samples;
+    }
+
+    pub fn total(&self) -> i64 {
+        return self.total;
+    }
+}
+
+impl CycleHook for SignalSampler {
+    fn on_cycle(&mut self, cpu: &Cpu) {
+        if self.schedule.contains(cpu.cycle) {
+            self.samples += 1;
+            self.total += cpu.signal_strength();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn step_calls_hooks_every_cycle() {
+        let program = vec![Op::Noop, Op::AddX(3)];
+        let mut cpu = Cpu::new();
+        let mut seen = vec![];
+
+        cpu.run(
+            &program,
+            &mut [&mut |cpu: &Cpu| seen.push((cpu.cycle, cpu.registers.x))],
+        )
+        .unwrap();
+
+        assert_eq!(vec![(1, 1), (2, 1), (3, 1)], seen);
+        assert_eq!(4, cpu.registers.x);
+        assert_eq!(2, cpu.pc);
+    }
+
+    #[test]
+    fn signal_sampler_is_correct() {
+        let program = vec![Op::AddX(4), Op::Noop];
+        let mut sampler = SignalSampler::new(&[2, 3]);
+
+        Cpu::new().run(&program, &mut [&mut sampler]).unwrap();
+
+        assert_eq!(2 + 3 * 5, sampler.total());
+    }
+
+    #[test]
+    fn signal_sampler_every_is_correct() {
+        let program = vec![Op::Noop; 10];
+        let mut every = SignalSampler::every(2, 3);
+        let mut until = SignalSampler::every(2, 3).until(7);
+
+        Cpu::new()
+            .run(&program, &mut [&mut every, &mut until])
+            .unwrap();
+
+        assert_eq!((3, 2 + 5 + 8), (every.samples(), every.total()));
+        assert_eq!((2, 2 + 5), (until.samples(), until.total()));
+    }
+
+    #[test]
+    fn run_stream_matches_run() {
+        let program = vec![Op::AddX(2), Op::Noop, Op::AddX(-5), Op::Noop];
+        let mut run = vec![];
+        let mut stream = vec![];
+
+        let mut cpu = Cpu::new();
+        cpu.run(
+            &program,
+            &mut [&mut |cpu: &Cpu| run.push(cpu.signal_strength())],
+        )
+        .unwrap();
+
+        let mut streamed = Cpu::new();
+        streamed
+            .run_stream(
+                program.iter().copied(),
+                &mut [&mut |cpu: &Cpu| stream.push(cpu.signal_strength())],
+            )
+            .unwrap();
+
+        assert_eq!(run, stream);
+        assert_eq!(
+            (cpu.cycle, cpu.registers),
+            (streamed.cycle, streamed.registers)
+        );
+    }
+
+    #[test]
+    fn run_stream_rejects_jumps() {
+        let program = vec![Op::AddX(2), Op::Noop, Op::Jnz(Register::X, 0), Op::Noop];
+        let mut cpu = Cpu::new();
+
+        assert_eq!(
+            Err(StreamJumpError { pc: 2 }),
+            cpu.run_stream(program, &mut [])
+        );
+        assert_eq!((3, 3), (cpu.cycle, cpu.registers.x));
+    }
+
+    #[test]
+    fn extended_ops_are_correct() {
+        // a = 3, x = 1; while a != 0 { x *= 2; a -= 1 }
+        let program = vec![
+            Op::Mov(Register::A, Operand::Value(3)),
+            Op::Mul(Register::X, Operand::Value(2)),
+            Op::Mov(Register::B, Operand::Register(Register::X)),
+            Op::Mov(Register::X, Operand::Register(Register::A)),
+            Op::AddX(-1),
+            Op::Mov(Register::A, Operand::Register(Register::X)),
+            Op::Mov(Register::X, Operand::Register(Register::B)),
+            Op::Jnz(Register::A, 1),
+            Op::Halt,
+            Op::AddX(100),
+        ];
+        let mut cpu = Cpu::new();
+
+        cpu.run(&program, &mut []).unwrap();
+
+        assert_eq!(8, cpu.registers.x);
+        assert!(cpu.halted);
+        assert_eq!(1 + 3 * (3 + 1 + 1 + 2 + 1 + 1 + 2) + 1, cpu.cycle);
+    }
+
+    #[test]
+    fn arithmetic_wraps_on_overflow() {
+        let program = vec![
+            Op::Mov(Register::A, Operand::Value(100_000)),
+            Op::Mul(Register::A, Operand::Register(Register::A)),
+            Op::Mov(Register::X, Operand::Value(i32::MAX)),
+            Op::AddX(1),
+        ];
+        let mut cpu = Cpu::new();
+
+        cpu.run(&program, &mut []).unwrap();
+
+        assert_eq!(100_000i32.wrapping_mul(100_000), cpu.registers.a);
+        assert_eq!(i32::MIN, cpu.registers.x);
+    }
+
+    #[test]
+    fn run_stops_at_cycle_limit() {
+        let program = vec![Op::Noop, Op::Jmp(0)];
+        let mut cpu = Cpu {
+            cycle_limit: 10,
+            ..Cpu::default()
+        };
+
+        assert_eq!(
+            Err(CycleLimitError { limit: 10, pc: 0 }),
+            cpu.run(&program, &mut [])
+        );
+        assert_eq!(10, cpu.cycle);
+    }
+
+    #[test]
+    fn run_has_no_cycle_limit_without_jumps() {
+        let program = vec![Op::AddX(1); 20];
+        let mut cpu = Cpu {
+            cycle_limit: 10,
+            ..Cpu::default()
+        };
+
+        assert_eq!(Ok(()), cpu.run(&program, &mut []));
+        assert_eq!((40, 21), (cpu.cycle, cpu.registers.x));
+    }
+
+    #[test]
+    fn tick_runs_one_cycle() {
+        let program = vec![Op::AddX(4)];
+        let mut cpu = Cpu::new();
+
+        assert!(cpu.tick(&program, &mut []));
+        assert_eq!(
+            (1, 0, 1, 1),
+            (cpu.cycle, cpu.pc, cpu.remaining, cpu.registers.x)
+        );
+
+        assert!(cpu.tick(&program, &mut []));
+        assert_eq!(
+            (2, 1, 0, 5),
+            (cpu.cycle, cpu.pc, cpu.remaining, cpu.registers.x)
+        );
+
+        assert!(!cpu.tick(&program, &mut []));
+    }
+}
@@ -0,0 +1,400 @@
+use super::cpu::{Cpu, CycleHook, Registers};
+use crate::netpbm::Bitmap;
+use crate::ocr::{self, OcrError};
+use std::collections::HashSet;
+use std::fmt;
+
+/// The geometry of the screen and the sprite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScreenConfig {
+    pub width: u32,
+    pub height: u32,
+    /// The sprite is this many pixels wide, with X at its middle, or just left of it when the
+    /// width is even.
+    pub sprite_width: u32,
+    /// Whether the beam starts over at the top left once the frame is full. Otherwise cycles
+    /// after the last pixel draw nothing.
+    pub wrap: bool,
+    /// The value of X before the program starts.
+    pub start_x: i32,
+}
+
+impl Default for ScreenConfig {
+    /// The screen of the puzzle: 40 by 6 with a 3 pixels wide sprite.
+    fn default() -> Self {
+        return ScreenConfig {
+            width: 40,
+            height: 6,
+            sprite_width: 3,
+            wrap: true,
+            start_x: 1,
+        };
+    }
+}
+
+/// Why a program does not fit the screen it draws on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeometryWarning {
+    /// The program ends before the beam has passed every pixel.
+    Underrun { cycles: usize, pixels: usize },
+    /// The program keeps running after the beam has passed every pixel.
+    Overrun { cycles: usize, pixels: usize },
+}
+
+impl fmt::Display for GeometryWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            GeometryWarning::Underrun { cycles, pixels } => write!(
+                f,
+                "the program runs for {} cycles, {} short of filling the {} pixels of the screen",
+                cycles,
+                pixels - cycles,
+                pixels
+            ),
+            GeometryWarning::Overrun { cycles, pixels } => write!(
+                f,
+                "the program runs for {} cycles, {} more than the {} pixels of the screen",
+                cycles,
+                cycles - pixels,
+                pixels
+            ),
+        };
+    }
+}
+
+impl ScreenConfig {
+    /// The number of pixels of the screen. It saturates where that does not fit in a usize,
+    /// which only happens on targets too small to hold such a screen anyway.
+    pub fn pixels(&self) -> usize {
+        return (self.width as usize).saturating_mul(self.height as usize);
+    }
+
+    /// A CPU about to run a program on this screen, with X at `start_x`.
+    pub fn cpu(&self) -> Cpu {
+        return Cpu::with_registers(Registers {
+            x: self.start_x,
+            ..Registers::default()
+        });
+    }
+
+    /// Compares the number of cycles a program runs for with the size of the frame.
+    pub fn check(&self, cycles: usize) -> Option<GeometryWarning> {
+        let pixels = self.pixels();
+
+        if cycles < pixels {
+            return Some(GeometryWarning::Underrun { cycles, pixels });
+        }
+
+        if cycles > pixels {
+            return Some(GeometryWarning::Overrun { cycles, pixels });
+        }
+
+        return None;
+    }
+
+    /// The pixel the beam draws during `cycle`, which starts at 1. None once the frame is
+    /// full, unless the beam wraps around, and always None on a screen without pixels.
+    pub fn beam(&self, cycle: usize) -> Option<(u32, u32)> {
+        let pixels = self.pixels();
+
+        if pixels == 0 || (!self.wrap && cycle > pixels) {
+            return None;
+        }
+
+        let position = (cycle - 1) % pixels;
+        let width = self.width as usize;
+
+        return Some(((position % width) as u32, (position / width) as u32));
+    }
+
+    /// Whether the sprite covers column `x` when the X register is `sprite_x`.
+    pub fn sprite_covers(&self, sprite_x: i32, x: u32) -> bool {
+        let left = sprite_x as i64 - (self.sprite_width as i64 - 1) / 2;
+
+        return (left..left + self.sprite_width as i64).contains(&(x as i64));
+    }
+}
+
+/// The screen the CPU draws on. The beam passes one pixel per cycle, row by row, and lights it
+/// when the sprite around X covers it.
+pub struct CRTScreen {
+    light: HashSet<(u32, u32)>,
+    config: ScreenConfig,
+}
+
+impl fmt::Display for CRTScreen {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut repr = String::from("");
+        for y in 0..self.config.height {
+            let mut line_repr = String::from("");
+            for x in 0..self.config.width {
+                if self.light.contains(&(x, y)) {
+                    line_repr += "#";
+                } else {
+                    line_repr += ".";
+                }
+            }
+
+            repr.push_str(&line_repr);
+            repr.push('\n');
+        }
+
+        return write!(f, "{}", repr);
+    }
+}
+
+impl CRTScreen {
+    /// A screen with the sprite and beam of the puzzle.
+    pub fn new(width: u32, height: u32) -> CRTScreen {
+        return CRTScreen::with_config(ScreenConfig {
+            width,
+            height,
+            ..ScreenConfig::default()
+        });
+    }
+
+    pub fn with_config(config: ScreenConfig) -> CRTScreen {
+        return CRTScreen {
+            light: HashSet::new(),
+            config,
+        };
+    }
+
+    pub fn config(&self) -> &ScreenConfig {
+        return &self.config;
+    }
+
+    fn draw_pixel(&mut self, x: u32, y: u32) {
+        self.light.insert((x, y));
+    }
+
+    /// The pixel the beam draws during `cycle`, see `ScreenConfig::beam`.
+    pub fn beam(&self, cycle: usize) -> Option<(u32, u32)> {
+        return self.config.beam(cycle);
+    }
+
+    /// The screen as it looks after `cycles` cycles, with the pixels the beam has not reached
+    /// yet left blank.
+    pub fn render_partial(&self, cycles: usize) -> String {
+        let mut repr = String::new();
+
+        for y in 0..self.config.height {
+            for x in 0..self.config.width {
+                let position = y as usize * self.config.width as usize + x as usize;
+
+                if position >= cycles {
+                    repr.push(' ');
+                } else if self.light.contains(&(x, y)) {
+                    repr.push('#');
+                } else {
+                    repr.push('.');
+                }
+            }
+
+            repr.push('\n');
+        }
+
+        return repr;
+    }
+
+    /// The screen as an image, with lit pixels on.
+    pub fn bitmap(&self) -> Bitmap {
+        return Bitmap::from_fn(self.config.width, self.config.height, |x, y| {
+            self.light.contains(&(x, y))
+        });
+    }
+
+    /// Reads the letters drawn on the screen.
+    pub fn read_text(&self) -> Result<String, OcrError> {
+        return ocr::read(self.config.width, self.config.height, |x, y| {
+            self.light.contains(&(x, y))
+        });
+    }
+}
+
+impl CycleHook for CRTScreen {
+    fn on_cycle(&mut self, cpu: &Cpu) {
+        if let Some((pixel_x, pixel_y)) = self.beam(cpu.cycle) {
+            if self.config.sprite_covers(cpu.registers.x, pixel_x) {
+                self.draw_pixel(pixel_x, pixel_y);
+            }
+        }
+    }
+}
+
+/// Draws on a screen and hands an image of it to `save` after every cycle, so that frames
+/// are written out as they are drawn instead of kept. Saving stops at the first error, which
+/// `finish` returns.
+pub struct FrameRecorder<F, E> {
+    screen: CRTScreen,
+    save: F,
+    frames: usize,
+    error: Option<E>,
+}
+
+impl<E, F: FnMut(usize, &Bitmap) -> Result<(), E>> FrameRecorder<F, E> {
+    /// Records `screen`, calling `save` with the number of every frame, the first one being
+    /// cycle 1.
+    pub fn new(screen: CRTScreen, save: F) -> FrameRecorder<F, E> {
+        return FrameRecorder {
+            screen,
+            save,
+            frames: 0,
+            error: None,
+        };
+    }
+
+    pub fn screen(&self) -> &CRTScreen {
+        return &self.screen;
+    }
+
+    /// The number of frames saved so far.
+    pub fn frames(&self) -> usize {
+        return self.frames;
+    }
+
+    /// Returns the screen, or the first error saving a frame.
+    pub fn finish(self) -> Result<CRTScreen, E> {
+        return match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.screen),
+        };
+    }
+}
+
+impl<E, F: FnMut(usize, &Bitmap) -> Result<(), E>> CycleHook for FrameRecorder<F, E> {
+    fn on_cycle(&mut self, cpu: &Cpu) {
+        self.screen.on_cycle(cpu);
+
+        if self.error.is_none() {
+            match (self.save)(self.frames + 1, &self.screen.bitmap()) {
+                Ok(()) => self.frames += 1,
+                Err(error) => self.error = Some(error),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day10::Op;
+
+    #[test]
+    fn beam_wraps_around() {
+        let screen = CRTScreen::new(40, 6);
+
+        assert_eq!(Some((0, 0)), screen.beam(1));
+        assert_eq!(Some((39, 0)), screen.beam(40));
+        assert_eq!(Some((0, 1)), screen.beam(41));
+        assert_eq!(Some((0, 0)), screen.beam(241));
+    }
+
+    #[test]
+    fn beam_stops_without_wrap() {
+        let screen = CRTScreen::with_config(ScreenConfig {
+            wrap: false,
+            ..ScreenConfig::default()
+        });
+
+        assert_eq!(Some((39, 5)), screen.beam(240));
+        assert_eq!(None, screen.beam(241));
+    }
+
+    #[test]
+    fn beam_handles_extreme_sizes() {
+        let huge = ScreenConfig {
+            width: 65_536,
+            height: 65_536,
+            ..ScreenConfig::default()
+        };
+        let empty = ScreenConfig {
+            width: 0,
+            ..ScreenConfig::default()
+        };
+
+        assert_eq!(1 << 32, huge.pixels());
+        assert_eq!(Some((0, 65_535)), huge.beam(65_535 * 65_536 + 1));
+        assert_eq!(None, empty.beam(1));
+    }
+
+    #[test]
+    fn sprite_covers_is_correct() {
+        let config = ScreenConfig {
+            sprite_width: 4,
+            ..ScreenConfig::default()
+        };
+
+        let covered: Vec<u32> = (0..10).filter(|x| config.sprite_covers(5, *x)).collect();
+
+        assert_eq!(vec![4, 5, 6, 7], covered);
+    }
+
+    #[test]
+    fn check_warns_about_program_length() {
+        let config = ScreenConfig::default();
+
+        assert_eq!(None, config.check(240));
+        assert_eq!(
+            Some(GeometryWarning::Underrun {
+                cycles: 200,
+                pixels: 240
+            }),
+            config.check(200)
+        );
+        assert_eq!(
+            "the program runs for 250 cycles, 10 more than the 240 pixels of the screen",
+            config.check(250).unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn render_partial_leaves_undrawn_pixels_blank() {
+        let mut screen = CRTScreen::new(3, 2);
+        screen.draw_pixel(0, 0);
+
+        assert_eq!("#..\n.  \n", screen.render_partial(4));
+    }
+
+    #[test]
+    fn frame_recorder_saves_every_cycle() {
+        let mut frames: Vec<(usize, String)> = vec![];
+        let mut recorder = FrameRecorder::new(CRTScreen::new(3, 1), |i, frame: &Bitmap| {
+            let row = (0..3)
+                .map(|x| if frame.get(x, 0) { '#' } else { '.' })
+                .collect();
+            frames.push((i, row));
+            return Ok::<(), ()>(());
+        });
+
+        Cpu::new()
+            .run(&[Op::AddX(1), Op::Noop], &mut [&mut recorder])
+            .unwrap();
+
+        assert_eq!(3, recorder.frames());
+        assert!(recorder.finish().is_ok());
+        assert_eq!(
+            vec![
+                (1, String::from("#..")),
+                (2, String::from("##.")),
+                (3, String::from("###"))
+            ],
+            frames
+        );
+    }
+
+    #[test]
+    fn frame_recorder_stops_at_first_error() {
+        let mut recorder = FrameRecorder::new(CRTScreen::new(3, 1), |i, _: &Bitmap| match i {
+            2 => Err(i),
+            _ => Ok(()),
+        });
+
+        Cpu::new()
+            .run(&[Op::AddX(1), Op::Noop], &mut [&mut recorder])
+            .unwrap();
+
+        assert_eq!(1, recorder.frames());
+        assert_eq!(Err(2), recorder.finish().map(|_| ()));
+    }
+}
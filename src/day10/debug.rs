@@ -0,0 +1,397 @@
+use super::asm::Program;
+use super::cpu::{Cpu, CycleHook, Instruction};
+use super::crt::{CRTScreen, ScreenConfig};
+use crate::error::{column_of, parse_number, ParseError};
+use std::fmt;
+use std::str::FromStr;
+
+const HELP: &str = "\
+step [N]        run N cycles (s)
+next [N]        run until N instructions have completed (n)
+continue        run until a breakpoint or the end of the program (c)
+break cycle N   stop during cycle N (b)
+break x N       stop when X changes to N
+delete          remove all breakpoints
+info            show the registers, the beam and the breakpoints (i)
+screen          show the screen drawn so far
+help            show this list
+quit            leave the debugger (q)
+Entering nothing repeats the last command.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Breakpoint {
+    Cycle(usize),
+    /// Stops on the cycle at whose end X has changed to this value.
+    X(i32),
+}
+
+impl Breakpoint {
+    fn hit(&self, cpu: &Cpu, previous_x: i32) -> bool {
+        return match self {
+            Breakpoint::Cycle(cycle) => cpu.cycle == *cycle,
+            Breakpoint::X(x) => cpu.registers.x == *x && previous_x != *x,
+        };
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            Breakpoint::Cycle(cycle) => write!(f, "cycle {}", cycle),
+            Breakpoint::X(x) => write!(f, "X = {}", x),
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugCommand {
+    StepCycles(usize),
+    StepInstructions(usize),
+    Continue,
+    Break(Breakpoint),
+    Delete,
+    Info,
+    Screen,
+    Help,
+    Quit,
+}
+
+impl FromStr for DebugCommand {
+    type Err = ParseError;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let split: Vec<&str> = str.split_whitespace().collect();
+
+        let command = match split.first() {
+            Some(command) => *command,
+            None => return Err(ParseError::missing_field("command", str, 1)),
+        };
+
+        let count = |index: usize| match split.get(index) {
+            Some(token) => parse_number(str, token),
+            None => Ok(1),
+        };
+
+        let arguments = match command {
+            "step" | "s" | "next" | "n" => 1,
+            "break" | "b" => 2,
+            _ => 0,
+        };
+
+        if let Some(token) = split.get(arguments + 1) {
+            return Err(ParseError::unexpected_token(
+                token,
+                column_of(str, token),
+                "end of line",
+            ));
+        }
+
+        return match command {
+            "step" | "s" => Ok(DebugCommand::StepCycles(count(1)?)),
+            "next" | "n" => Ok(DebugCommand::StepInstructions(count(1)?)),
+            "continue" | "c" => Ok(DebugCommand::Continue),
+            "break" | "b" => {
+                let (kind, value) = match (split.get(1), split.get(2)) {
+                    (Some(kind), Some(value)) => (*kind, *value),
+                    _ => return Err(ParseError::missing_field("breakpoint", str, str.len() + 1)),
+                };
+
+                match kind {
+                    "cycle" => Ok(DebugCommand::Break(Breakpoint::Cycle(parse_number(
+                        str, value,
+                    )?))),
+                    "x" | "X" => Ok(DebugCommand::Break(Breakpoint::X(parse_number(
+                        str, value,
+                    )?))),
+                    _ => Err(ParseError::unexpected_token(
+                        kind,
+                        column_of(str, kind),
+                        "cycle or x",
+                    )),
+                }
+            }
+            "delete" => Ok(DebugCommand::Delete),
+            "info" | "i" => Ok(DebugCommand::Info),
+            "screen" => Ok(DebugCommand::Screen),
+            "help" | "h" => Ok(DebugCommand::Help),
+            "quit" | "q" => Ok(DebugCommand::Quit),
+            _ => Err(ParseError::unexpected_token(
+                command,
+                column_of(str, command),
+                "a command, try help",
+            )),
+        };
+    }
+}
+
+/// Why running stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    /// Ran as far as asked.
+    Done,
+    Breakpoint(Breakpoint),
+    Ended,
+    /// Continued up to the cycle limit of the CPU without ending.
+    CycleLimit,
+}
+
+/// Runs a program on a screen a cycle at a time, stopping at breakpoints.
+pub struct Debugger<'a> {
+    program: &'a Program,
+    cpu: Cpu,
+    screen: CRTScreen,
+    breakpoints: Vec<Breakpoint>,
+}
+
+impl<'a> Debugger<'a> {
+    /// Debugs `program` on the screen of the puzzle.
+    pub fn new(program: &'a Program) -> Debugger<'a> {
+        return Debugger::with_config(program, ScreenConfig::default());
+    }
+
+    pub fn with_config(program: &'a Program, config: ScreenConfig) -> Debugger<'a> {
+        return Debugger {
+            program,
+            cpu: config.cpu(),
+            screen: CRTScreen::with_config(config),
+            breakpoints: vec![],
+        };
+    }
+
+    pub fn cpu(&self) -> &Cpu {
+        return &self.cpu;
+    }
+
+    pub fn screen(&self) -> &CRTScreen {
+        return &self.screen;
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        if !self.breakpoints.contains(&breakpoint) {
+            self.breakpoints.push(breakpoint);
+        }
+    }
+
+    /// Runs one cycle, and says why it has to stop afterwards, if it has to.
+    fn cycle(&mut self) -> Option<Stop> {
+        let previous_x = self.cpu.registers.x;
+        let mut hooks: [&mut dyn CycleHook; 1] = [&mut self.screen];
+
+        if !self.cpu.tick(&self.program.ops, &mut hooks) {
+            return Some(Stop::Ended);
+        }
+
+        return self
+            .breakpoints
+            .iter()
+            .find(|x| x.hit(&self.cpu, previous_x))
+            .map(|x| Stop::Breakpoint(*x));
+    }
+
+    pub fn step_cycles(&mut self, count: usize) -> Stop {
+        for _ in 0..count {
+            if let Some(stop) = self.cycle() {
+                return stop;
+            }
+        }
+
+        return Stop::Done;
+    }
+
+    pub fn step_instructions(&mut self, count: usize) -> Stop {
+        for _ in 0..count {
+            loop {
+                if let Some(stop) = self.cycle() {
+                    return stop;
+                }
+
+                if self.cpu.remaining == 0 {
+                    break;
+                }
+            }
+        }
+
+        return Stop::Done;
+    }
+
+    pub fn resume(&mut self) -> Stop {
+        let limited = Cpu::may_loop(&self.program.ops);
+
+        loop {
+            if limited && self.cpu.cycle >= self.cpu.cycle_limit {
+                return Stop::CycleLimit;
+            }
+
+            if let Some(stop) = self.cycle() {
+                return stop;
+            }
+        }
+    }
+
+    /// One line with the cycle, the registers, the instruction at the program counter and the
+    /// position of the beam.
+    pub fn status(&self) -> String {
+        let mut status = format!(
+            "cycle {} | X {} | pc {}",
+            self.cpu.cycle, self.cpu.registers.x, self.cpu.pc
+        );
+
+        match self.program.ops.get(self.cpu.pc) {
+            _ if self.cpu.halted => status += " halted",
+            Some(op) => {
+                status += &format!(" `{}`", op);
+
+                if let Some(span) = self.program.spans.get(self.cpu.pc) {
+                    status += &format!(" (line {})", span.line);
+                }
+
+                if self.cpu.remaining > 0 {
+                    status += &format!(", {} of {} cycles left", self.cpu.remaining, op.cycles());
+                }
+            }
+            None => status += " end of program",
+        }
+
+        if self.cpu.cycle > 0 {
+            match self.screen.beam(self.cpu.cycle) {
+                Some((x, y)) => status += &format!(" | beam ({}, {})", x, y),
+                None => status += " | beam off screen",
+            }
+        }
+
+        return status;
+    }
+
+    /// Runs `command` and returns what to show for it. `Quit` is left to the caller.
+    pub fn run_command(&mut self, command: &DebugCommand) -> String {
+        let stop = match command {
+            DebugCommand::StepCycles(count) => self.step_cycles(*count),
+            DebugCommand::StepInstructions(count) => self.step_instructions(*count),
+            DebugCommand::Continue => self.resume(),
+            DebugCommand::Break(breakpoint) => {
+                self.add_breakpoint(*breakpoint);
+                return format!("breakpoint at {}", breakpoint);
+            }
+            DebugCommand::Delete => {
+                self.breakpoints.clear();
+                return String::from("deleted all breakpoints");
+            }
+            DebugCommand::Info => {
+                let registers = self.cpu.registers;
+                let mut info = self.status();
+
+                info += &format!(
+                    "\nA {} | B {} | C {}",
+                    registers.a, registers.b, registers.c
+                );
+
+                for (i, breakpoint) in self.breakpoints.iter().enumerate() {
+                    info += &format!("\nbreakpoint {}: {}", i + 1, breakpoint);
+                }
+
+                return info;
+            }
+            DebugCommand::Screen => return self.screen.render_partial(self.cpu.cycle),
+            DebugCommand::Help => return String::from(HELP),
+            DebugCommand::Quit => return String::new(),
+        };
+
+        let mut output = match stop {
+            Stop::Done => String::new(),
+            Stop::Breakpoint(breakpoint) => format!("stopped at {}\n", breakpoint),
+            Stop::Ended => String::from("the program has ended\n"),
+            Stop::CycleLimit => format!(
+                "stopped after {} cycles, the program may loop forever\n",
+                self.cpu.cycle_limit
+            ),
+        };
+
+        output += &self.status();
+        output.push('\n');
+        output += &self.screen.render_partial(self.cpu.cycle);
+
+        return output;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day10::assemble;
+
+    #[test]
+    fn parse_command_is_correct() {
+        assert_eq!(Ok(DebugCommand::StepCycles(1)), "s".parse());
+        assert_eq!(Ok(DebugCommand::StepInstructions(3)), "next 3".parse());
+        assert_eq!(Ok(DebugCommand::Break(Breakpoint::X(-2))), "b x -2".parse());
+        assert_eq!(
+            Err(ParseError::unexpected_token("pc", 7, "cycle or x")),
+            "break pc 3".parse::<DebugCommand>()
+        );
+    }
+
+    #[test]
+    fn steps_by_cycle_and_instruction() {
+        let program = assemble("addx 3\nnoop\naddx -1").unwrap();
+        let mut debugger = Debugger::new(&program);
+
+        assert_eq!(Stop::Done, debugger.step_cycles(1));
+        assert_eq!(
+            (1, 0, 1),
+            (
+                debugger.cpu().cycle,
+                debugger.cpu().pc,
+                debugger.cpu().remaining
+            )
+        );
+
+        assert_eq!(Stop::Done, debugger.step_instructions(2));
+        assert_eq!(
+            (3, 2, 4),
+            (
+                debugger.cpu().cycle,
+                debugger.cpu().pc,
+                debugger.cpu().registers.x
+            )
+        );
+
+        assert_eq!(Stop::Ended, debugger.resume());
+        assert_eq!(3, debugger.cpu().registers.x);
+    }
+
+    #[test]
+    fn stops_at_breakpoints() {
+        let program = assemble("addx 3\nnoop\naddx -1\nnoop").unwrap();
+        let mut debugger = Debugger::new(&program);
+        debugger.add_breakpoint(Breakpoint::X(3));
+        debugger.add_breakpoint(Breakpoint::Cycle(4));
+
+        assert_eq!(Stop::Breakpoint(Breakpoint::Cycle(4)), debugger.resume());
+        assert_eq!(Stop::Breakpoint(Breakpoint::X(3)), debugger.resume());
+        assert_eq!(5, debugger.cpu().cycle);
+        assert_eq!(Stop::Ended, debugger.resume());
+    }
+
+    #[test]
+    fn resume_stops_at_cycle_limit() {
+        let program = assemble("loop: jmp loop").unwrap();
+        let mut debugger = Debugger::new(&program);
+        debugger.cpu.cycle_limit = 5;
+
+        assert_eq!(Stop::CycleLimit, debugger.resume());
+        assert_eq!(5, debugger.cpu().cycle);
+    }
+
+    #[test]
+    fn status_is_correct() {
+        let program = assemble("noop\naddx 3").unwrap();
+        let mut debugger = Debugger::new(&program);
+
+        debugger.step_cycles(2);
+
+        assert_eq!(
+            "cycle 2 | X 1 | pc 1 `addx 3` (line 2), 1 of 2 cycles left | beam (1, 0)",
+            debugger.status()
+        );
+    }
+}
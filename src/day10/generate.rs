@@ -0,0 +1,254 @@
+use super::asm::Program;
+use super::cpu::Op;
+use super::crt::ScreenConfig;
+use crate::netpbm::Bitmap;
+use crate::ocr::{self, OcrError};
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GenerateError {
+    /// The target is not the size of the screen.
+    WrongSize { width: u32, height: u32 },
+    /// The text has a letter that is not in the font.
+    Text(OcrError),
+    /// No program can draw the pixel at `x`, `y` after drawing the ones before it, the sprite
+    /// cannot get into place in time.
+    Unreachable { x: u32, y: u32 },
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            GenerateError::WrongSize { width, height } => {
+                write!(
+                    f,
+                    "the target is {}x{} pixels, not the size of the screen",
+                    width, height
+                )
+            }
+            GenerateError::Text(error) => write!(f, "{}", error),
+            GenerateError::Unreachable { x, y } => write!(
+                f,
+                "no program draws pixel ({}, {}) as well as the ones before it",
+                x, y
+            ),
+        };
+    }
+}
+
+impl Error for GenerateError {}
+
+/// How a state of the search was reached: from X `from`, with `op`.
+#[derive(Debug, Clone, Copy)]
+struct Step {
+    from: i32,
+    op: Op,
+}
+
+/// Writes a program of `addx` and `noop` that draws exactly `target` on a screen configured
+/// as `config`, and runs for as many cycles as the screen has pixels.
+///
+/// Every cycle lights one pixel or leaves it dark depending on X, and X only changes at the
+/// end of an `addx`, so this searches cycle by cycle for the values X can hold while the
+/// screen still matches the target.
+pub fn generate(target: &Bitmap, config: ScreenConfig) -> Result<Program, GenerateError> {
+    if (target.width(), target.height()) != (config.width, config.height) {
+        return Err(GenerateError::WrongSize {
+            width: target.width(),
+            height: target.height(),
+        });
+    }
+
+    // Any X further off the screen than this draws the same as one of the bounds
+    let margin = config.sprite_width as i32 + 1;
+    let (low, high) = (
+        (-margin).min(config.start_x),
+        (config.width as i32 + margin).max(config.start_x),
+    );
+    let xs = (high - low + 1) as usize;
+    let pixels = config.pixels();
+
+    let position = |cycle: usize| {
+        let pixel = cycle as u32;
+        return (pixel % config.width, pixel / config.width);
+    };
+    let matches = |cycle: usize, sprite_x: i32| {
+        let (x, y) = position(cycle);
+        return config.sprite_covers(sprite_x, x) == target.get(x, y);
+    };
+
+    // steps[cycle][x] is set when X can be x before `cycle` with the screen matching so far
+    let mut steps: Vec<Vec<Option<Step>>> = vec![vec![None; xs]; pixels + 1];
+    steps[0][(config.start_x - low) as usize] = Some(Step {
+        from: config.start_x,
+        op: Op::Noop,
+    });
+
+    for cycle in 0..pixels {
+        for x in low..=high {
+            if steps[cycle][(x - low) as usize].is_none() || !matches(cycle, x) {
+                continue;
+            }
+
+            let next = &mut steps[cycle + 1][(x - low) as usize];
+            if next.is_none() {
+                *next = Some(Step {
+                    from: x,
+                    op: Op::Noop,
+                });
+            }
+
+            if cycle + 2 > pixels || !matches(cycle + 1, x) {
+                continue;
+            }
+
+            for to in low..=high {
+                let next = &mut steps[cycle + 2][(to - low) as usize];
+                if next.is_none() {
+                    *next = Some(Step {
+                        from: x,
+                        op: Op::AddX(to - x),
+                    });
+                }
+            }
+        }
+    }
+
+    let mut x = match (low..=high).find(|x| steps[pixels][(x - low) as usize].is_some()) {
+        Some(x) => x,
+        None => {
+            let cycle = (0..pixels)
+                .rev()
+                .find(|cycle| steps[*cycle].iter().any(|x| x.is_some()))
+                .unwrap_or(0);
+            let (x, y) = position(cycle);
+
+            return Err(GenerateError::Unreachable { x, y });
+        }
+    };
+
+    let mut ops = vec![];
+    let mut cycle = pixels;
+
+    while cycle > 0 {
+        let step = steps[cycle][(x - low) as usize].unwrap();
+
+        ops.push(step.op);
+        cycle -= match step.op {
+            Op::AddX(_) => 2,
+            _ => 1,
+        };
+        x = step.from;
+    }
+
+    ops.reverse();
+
+    return Ok(Program::from_ops(ops));
+}
+
+/// Writes a program that draws `text` in the block letters of the puzzle, see `generate`.
+pub fn generate_text(text: &str, config: ScreenConfig) -> Result<Program, GenerateError> {
+    let rendered = ocr::render(text).map_err(GenerateError::Text)?;
+
+    if rendered.width() > config.width + 1 || rendered.height() > config.height {
+        return Err(GenerateError::WrongSize {
+            width: rendered.width(),
+            height: rendered.height(),
+        });
+    }
+
+    // The last glyph's blank column may fall off the edge
+    let target = Bitmap::from_fn(config.width, config.height, |x, y| {
+        x < rendered.width() && y < rendered.height() && rendered.get(x, y)
+    });
+
+    return generate(&target, config);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day10::{cycle_count, draw, Instruction};
+
+    /// A small xorshift generator, so the tests are random but repeatable.
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self, below: u64) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            return self.0 % below;
+        }
+    }
+
+    #[test]
+    fn generate_round_trips_random_screens() {
+        let config = ScreenConfig::default();
+        let mut random = Random(0x2022_1210);
+
+        for _ in 0..50 {
+            // Only a screen drawn by some program in one pass is sure to be drawable
+            let mut ops = vec![];
+            let mut cycles = 0;
+            while cycles < 240 {
+                let op = match random.next(3) {
+                    0 => Op::Noop,
+                    _ if cycles == 239 => Op::Noop,
+                    _ => Op::AddX(random.next(15) as i32 - 7),
+                };
+                cycles += op.cycles();
+                ops.push(op);
+            }
+            let target = draw(&Program::from_ops(ops), config).unwrap().bitmap();
+
+            let program = generate(&target, config).unwrap();
+
+            assert_eq!(target, draw(&program, config).unwrap().bitmap());
+            assert_eq!(Ok(240), cycle_count(&program, config));
+        }
+    }
+
+    #[test]
+    fn generate_text_round_trips_random_words() {
+        let config = ScreenConfig::default();
+        let mut random = Random(0x5EED);
+        // With X starting at 1 the first two pixels are always lit
+        let first = ['B', 'E', 'F', 'P', 'R', 'Z'];
+        let letters: Vec<char> = "ABCEFGHIJKLOPRSUZ".chars().collect();
+
+        for _ in 0..20 {
+            let mut text = String::from(first[random.next(first.len() as u64) as usize]);
+            for _ in 1..8 {
+                text.push(letters[random.next(letters.len() as u64) as usize]);
+            }
+
+            let program = generate_text(&text, config).unwrap();
+
+            assert_eq!(Ok(text), draw(&program, config).unwrap().read_text());
+        }
+    }
+
+    #[test]
+    fn generate_reports_errors() {
+        let config = ScreenConfig::default();
+        let target = Bitmap::new(40, 6);
+
+        assert_eq!(
+            Err(GenerateError::Unreachable { x: 0, y: 0 }),
+            generate(&target, config)
+        );
+        assert_eq!(
+            Err(GenerateError::WrongSize {
+                width: 45,
+                height: 6
+            }),
+            generate_text("ABCEFGHIJ", config)
+        );
+        assert_eq!(
+            Err(GenerateError::Text(OcrError::UnknownLetter { letter: 'D' })),
+            generate_text("D", config)
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use super::asm::Program;
+use super::cpu::{Cpu, CycleHook};
+use super::crt::ScreenConfig;
+use serde::Serialize;
+use std::io::{self, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// Comma separated values, with a header line.
+    Csv,
+    /// One JSON object per line.
+    JsonLines,
+}
+
+/// What happened during one cycle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TraceRow {
+    pub cycle: usize,
+    pub pc: usize,
+    /// The instruction in progress, as written by the disassembler.
+    pub instruction: String,
+    pub x_during: i32,
+    pub x_after: i32,
+    pub signal_strength: i64,
+    /// The pixel the beam drew, missing once it has left a screen that does not wrap.
+    pub pixel_x: Option<u32>,
+    pub pixel_y: Option<u32>,
+    /// Whether the sprite covered the pixel, so that it was lit.
+    pub lit: bool,
+}
+
+impl TraceRow {
+    const CSV_HEADER: &'static str =
+        "cycle,pc,instruction,x_during,x_after,signal_strength,pixel_x,pixel_y,lit";
+
+    fn to_csv(&self) -> String {
+        let optional = |value: Option<u32>| value.map(|x| x.to_string()).unwrap_or_default();
+
+        return format!(
+            "{},{},{},{},{},{},{},{},{}",
+            self.cycle,
+            self.pc,
+            self.instruction,
+            self.x_during,
+            self.x_after,
+            self.signal_strength,
+            optional(self.pixel_x),
+            optional(self.pixel_y),
+            self.lit
+        );
+    }
+}
+
+/// Writes a `TraceRow` for every cycle of `program` as it runs. Rows are written as soon as
+/// the cycle ends, so nothing is kept in memory. Writing stops at the first error, which
+/// `finish` returns.
+pub struct Tracer<'a, W: Write> {
+    program: &'a Program,
+    config: ScreenConfig,
+    format: TraceFormat,
+    writer: W,
+    row: Option<TraceRow>,
+    error: Option<io::Error>,
+}
+
+impl<'a, W: Write> Tracer<'a, W> {
+    /// Traces `program` drawing on a screen configured as `config`.
+    pub fn new(program: &'a Program, config: ScreenConfig, format: TraceFormat, writer: W) -> Self {
+        let mut tracer = Tracer {
+            program,
+            config,
+            format,
+            writer,
+            row: None,
+            error: None,
+        };
+
+        if format == TraceFormat::Csv {
+            tracer.write(String::from(TraceRow::CSV_HEADER));
+        }
+
+        return tracer;
+    }
+
+    fn write(&mut self, line: String) {
+        if self.error.is_none() {
+            if let Err(error) = writeln!(self.writer, "{}", line) {
+                self.error = Some(error);
+            }
+        }
+    }
+
+    /// Flushes the writer and returns it, or the first error writing the trace.
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        self.writer.flush()?;
+
+        return Ok(self.writer);
+    }
+}
+
+impl<W: Write> CycleHook for Tracer<'_, W> {
+    fn on_cycle(&mut self, cpu: &Cpu) {
+        let pixel = self.config.beam(cpu.cycle);
+
+        self.row = Some(TraceRow {
+            cycle: cpu.cycle,
+            pc: cpu.pc,
+            instruction: self
+                .program
+                .ops
+                .get(cpu.pc)
+                .map(|x| x.to_string())
+                .unwrap_or_default(),
+            x_during: cpu.registers.x,
+            x_after: cpu.registers.x,
+            signal_strength: cpu.signal_strength(),
+            pixel_x: pixel.map(|(x, _)| x),
+            pixel_y: pixel.map(|(_, y)| y),
+            lit: pixel.is_some_and(|(x, _)| self.config.sprite_covers(cpu.registers.x, x)),
+        });
+    }
+
+    fn after_cycle(&mut self, cpu: &Cpu) {
+        let mut row = match self.row.take() {
+            Some(row) => row,
+            None => return,
+        };
+
+        row.x_after = cpu.registers.x;
+
+        let line = match self.format {
+            TraceFormat::Csv => row.to_csv(),
+            TraceFormat::JsonLines => serde_json::to_string(&row).expect("rows always serialize"),
+        };
+
+        self.write(line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day10::assemble;
+
+    fn trace(format: TraceFormat) -> String {
+        let program = assemble("noop\naddx 5").unwrap();
+        let config = ScreenConfig {
+            width: 2,
+            height: 1,
+            wrap: false,
+            ..ScreenConfig::default()
+        };
+        let mut tracer = Tracer::new(&program, config, format, vec![]);
+
+        Cpu::new().run(&program.ops, &mut [&mut tracer]).unwrap();
+
+        return String::from_utf8(tracer.finish().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn csv_trace_is_correct() {
+        let expected = "\
+cycle,pc,instruction,x_during,x_after,signal_strength,pixel_x,pixel_y,lit
+1,0,noop,1,1,1,0,0,true
+2,1,addx 5,1,1,2,1,0,true
+3,1,addx 5,1,6,3,,,false
+";
+
+        assert_eq!(expected, trace(TraceFormat::Csv));
+    }
+
+    #[test]
+    fn json_lines_trace_is_correct() {
+        let lines: Vec<serde_json::Value> = trace(TraceFormat::JsonLines)
+            .lines()
+            .map(|x| serde_json::from_str(x).unwrap())
+            .collect();
+
+        assert_eq!(3, lines.len());
+        assert_eq!("addx 5", lines[2]["instruction"]);
+        assert_eq!(6, lines[2]["x_after"]);
+        assert!(lines[2]["pixel_x"].is_null());
+    }
+}
@@ -1,16 +1,38 @@
-use crate::io_utils::read_lines;
+use crate::error::{column_of, ParseError};
+use crate::io_utils::Input;
+use crate::solution::{Answer, Solution};
 use std::str::FromStr;
-use std::string::ParseError;
 
-#[derive(Debug, PartialEq)]
-enum Shape {
+pub struct Day2;
+
+impl Solution for Day2 {
+    const DAY: u8 = 2;
+    const TITLE: &'static str = "Rock Paper Scissors";
+
+    type Input = StrategyGuide;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        return parse(input);
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer, String> {
+        return Ok(part1(input).into());
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer, String> {
+        return Ok(part2(input).into());
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Shape {
     Rock,
     Paper,
     Scissors,
 }
 
 impl Shape {
-    fn score(&self) -> u32 {
+    pub fn score(&self) -> u32 {
         return match self {
             Shape::Rock => 1,
             Shape::Paper => 2,
@@ -18,7 +40,7 @@ impl Shape {
         };
     }
 
-    fn loses_to(&self) -> Shape {
+    pub fn loses_to(&self) -> Shape {
         return match self {
             Shape::Rock => Shape::Paper,
             Shape::Paper => Shape::Scissors,
@@ -26,7 +48,7 @@ impl Shape {
         };
     }
 
-    fn wins_over(&self) -> Shape {
+    pub fn wins_over(&self) -> Shape {
         return match self {
             Shape::Rock => Shape::Scissors,
             Shape::Paper => Shape::Rock,
@@ -35,20 +57,28 @@ impl Shape {
     }
 }
 
-#[derive(Debug, PartialEq)]
-enum GameResult {
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum GameResult {
     Win,
     Draw,
     Loss,
 }
 
-struct Part1Instruction {
+/// A round as part 1 reads it: the second column is the shape to play.
+pub struct Part1Instruction {
     opponent_shape: Shape,
     my_shape: Shape,
 }
 
 impl Part1Instruction {
-    fn score(&self) -> u32 {
+    pub fn new(opponent_shape: Shape, my_shape: Shape) -> Part1Instruction {
+        return Part1Instruction {
+            opponent_shape,
+            my_shape,
+        };
+    }
+
+    pub fn score(&self) -> u32 {
         return self.my_shape.score() + self.outcome_score();
     }
 
@@ -73,23 +103,60 @@ impl Part1Instruction {
     }
 }
 
+/// Splits a line of the strategy guide into its two columns.
+fn split_columns(str: &str) -> Result<(&str, &str), ParseError> {
+    let split: Vec<&str> = str.split(" ").collect();
+
+    if split[0].is_empty() {
+        return Err(ParseError::missing_field("opponent shape", str, 1));
+    }
+
+    if split.len() < 2 || split[1].is_empty() {
+        return Err(ParseError::missing_field(
+            "second column",
+            str,
+            str.len() + 1,
+        ));
+    }
+
+    if split.len() > 2 {
+        return Err(ParseError::unexpected_token(
+            split[2],
+            column_of(str, split[2]),
+            "end of line",
+        ));
+    }
+
+    return Ok((split[0], split[1]));
+}
+
+fn parse_opponent_shape(str: &str, column: usize) -> Result<Shape, ParseError> {
+    return match str {
+        "A" => Ok(Shape::Rock),
+        "B" => Ok(Shape::Paper),
+        "C" => Ok(Shape::Scissors),
+        &_ => Err(ParseError::unexpected_token(str, column, "A, B or C")),
+    };
+}
+
 impl FromStr for Part1Instruction {
     type Err = ParseError;
     fn from_str(str: &str) -> Result<Self, Self::Err> {
-        let split: Vec<&str> = str.split(" ").collect();
+        let (first, second) = split_columns(str)?;
 
-        let opponent_shape: Shape = match split[0] {
-            "A" => Shape::Rock,
-            "B" => Shape::Paper,
-            "C" => Shape::Scissors,
-            &_ => panic!("DING"),
-        };
+        let opponent_shape = parse_opponent_shape(first, column_of(str, first))?;
 
-        let my_shape: Shape = match split[1] {
+        let my_shape: Shape = match second {
             "X" => Shape::Rock,
             "Y" => Shape::Paper,
             "Z" => Shape::Scissors,
-            &_ => panic!("DONG"),
+            &_ => {
+                return Err(ParseError::unexpected_token(
+                    second,
+                    column_of(str, second),
+                    "X, Y or Z",
+                ))
+            }
         };
 
         return Ok(Part1Instruction {
@@ -99,21 +166,41 @@ impl FromStr for Part1Instruction {
     }
 }
 
-fn part1(filename: &str) -> u32 {
-    let input_lines: Vec<Part1Instruction> = read_lines(filename)
-        .unwrap()
-        .map(|x| x.unwrap())
-        .map(|x| x.parse().unwrap())
-        .collect();
+/// The encrypted strategy guide, read both the way part 1 and the way part 2 understands it.
+pub struct StrategyGuide {
+    part1: Vec<Part1Instruction>,
+    part2: Vec<Part2Instruction>,
+}
 
-    let scores: Vec<u32> = input_lines.into_iter().map(|x| x.score()).collect();
+impl StrategyGuide {
+    pub fn part1_rounds(&self) -> &[Part1Instruction] {
+        return &self.part1;
+    }
 
-    println!("{:?}", scores);
+    pub fn part2_rounds(&self) -> &[Part2Instruction] {
+        return &self.part2;
+    }
+}
+
+pub fn parse(input: &str) -> Result<StrategyGuide, ParseError> {
+    let input = Input::from_text(input);
+
+    return Ok(StrategyGuide {
+        part1: input.parse_lines()?,
+        part2: input.parse_lines()?,
+    });
+}
+
+pub fn part1(guide: &StrategyGuide) -> u32 {
+    let scores: Vec<u32> = guide.part1.iter().map(|x| x.score()).collect();
+
+    log::debug!("scores: {:?}", scores);
 
     return scores.into_iter().sum();
 }
 
-struct Part2Instruction {
+/// A round as part 2 reads it: the second column is how the round has to end.
+pub struct Part2Instruction {
     result: GameResult,
     opponent_shape: Shape,
 }
@@ -121,20 +208,21 @@ struct Part2Instruction {
 impl FromStr for Part2Instruction {
     type Err = ParseError;
     fn from_str(str: &str) -> Result<Self, Self::Err> {
-        let split: Vec<&str> = str.split(" ").collect();
+        let (first, second) = split_columns(str)?;
 
-        let opponent_shape: Shape = match split[0] {
-            "A" => Shape::Rock,
-            "B" => Shape::Paper,
-            "C" => Shape::Scissors,
-            &_ => panic!("DING"),
-        };
+        let opponent_shape = parse_opponent_shape(first, column_of(str, first))?;
 
-        let result: GameResult = match split[1] {
+        let result: GameResult = match second {
             "X" => GameResult::Loss,
             "Y" => GameResult::Draw,
             "Z" => GameResult::Win,
-            &_ => panic!("DONG"),
+            &_ => {
+                return Err(ParseError::unexpected_token(
+                    second,
+                    column_of(str, second),
+                    "X, Y or Z",
+                ))
+            }
         };
 
         return Ok(Part2Instruction {
@@ -145,23 +233,24 @@ impl FromStr for Part2Instruction {
 }
 
 impl Part2Instruction {
-    fn score(&self) -> u32 {
+    pub fn new(opponent_shape: Shape, result: GameResult) -> Part2Instruction {
+        return Part2Instruction {
+            result,
+            opponent_shape,
+        };
+    }
+
+    pub fn score(&self) -> u32 {
         return match self.result {
             GameResult::Win => 6 + self.opponent_shape.loses_to().score(),
-            GameResult::Loss => 0 + self.opponent_shape.wins_over().score(),
+            GameResult::Loss => self.opponent_shape.wins_over().score(),
             GameResult::Draw => 3 + self.opponent_shape.score(),
         };
     }
 }
 
-fn part2(filename: &str) -> u32 {
-    let input_lines: Vec<Part2Instruction> = read_lines(filename)
-        .unwrap()
-        .map(|x| x.unwrap())
-        .map(|x| x.parse().unwrap())
-        .collect();
-
-    let scores: Vec<u32> = input_lines.into_iter().map(|x| x.score()).collect();
+pub fn part2(guide: &StrategyGuide) -> u32 {
+    let scores: Vec<u32> = guide.part2.iter().map(|x| x.score()).collect();
 
     return scores.into_iter().sum();
 }
@@ -169,6 +258,7 @@ fn part2(filename: &str) -> u32 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::io_utils::parse_file;
 
     #[test]
     fn parse_input_line_is_correct() {
@@ -179,30 +269,36 @@ mod tests {
     }
 
     #[test]
-    fn part1_example_is_correct() {
-        let result = part1("./data/day2_example.txt");
+    fn parse_input_line_with_unknown_shape_fails() {
+        let result = "A W".parse::<Part1Instruction>();
 
-        assert_eq!(15, result);
+        assert_eq!(
+            Err(ParseError::unexpected_token("W", 3, "X, Y or Z")),
+            result.map(|_| ())
+        );
     }
 
     #[test]
-    fn part1_is_correct() {
-        let result = part1("./data/day2.txt");
+    fn parse_reports_line_number() {
+        let error = parse("A Y\nB\n").err().unwrap();
 
-        assert_eq!(12276, result);
+        assert_eq!(
+            ParseError::missing_field("second column", "B", 2).at_line(2),
+            error
+        );
     }
 
     #[test]
-    fn part2_example_is_correct() {
-        let result = part2("./data/day2_example.txt");
+    fn part1_example_is_correct() {
+        let result = part1(&parse_file("./data/day2_example.txt", parse));
 
-        assert_eq!(12, result);
+        assert_eq!(15, result);
     }
 
     #[test]
-    fn part2_is_correct() {
-        let result = part2("./data/day2.txt");
+    fn part2_example_is_correct() {
+        let result = part2(&parse_file("./data/day2_example.txt", parse));
 
-        assert_eq!(9975, result);
+        assert_eq!(12, result);
     }
 }
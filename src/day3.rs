@@ -1,21 +1,44 @@
-use crate::io_utils::read_lines;
+use crate::error::ParseError;
+use crate::io_utils::Input;
+use crate::solution::{Answer, Solution};
 use std::collections::HashSet;
 use std::str::FromStr;
 
-struct Rucksack {
+pub struct Day3;
+
+impl Solution for Day3 {
+    const DAY: u8 = 3;
+    const TITLE: &'static str = "Rucksack Reorganization";
+
+    type Input = Vec<Rucksack>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        return parse(input);
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer, String> {
+        return Ok(part1(input).into());
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer, String> {
+        return Ok(part2(input).into());
+    }
+}
+
+pub struct Rucksack {
     items: String,
 }
 
 impl Rucksack {
-    fn first_compartment(&self) -> String {
+    pub fn first_compartment(&self) -> String {
         return String::from(&self.items[0..self.items.len() / 2]);
     }
 
-    fn second_compartment(&self) -> String {
+    pub fn second_compartment(&self) -> String {
         return String::from(&self.items[self.items.len() / 2..self.items.len()]);
     }
 
-    fn items_in_both_compartments(&self) -> HashSet<char> {
+    pub fn items_in_both_compartments(&self) -> HashSet<char> {
         let first_compartment = self.first_compartment();
         let second_compartment = self.second_compartment();
 
@@ -30,41 +53,50 @@ impl Rucksack {
         return items_in_both;
     }
 }
-struct RucksackParseError {}
+
 impl FromStr for Rucksack {
-    type Err = RucksackParseError;
+    type Err = ParseError;
     fn from_str(str: &str) -> Result<Self, Self::Err> {
+        for (i, c) in str.char_indices() {
+            if priority(c).is_none() {
+                return Err(ParseError::unexpected_token(
+                    &str[i..i + c.len_utf8()],
+                    i + 1,
+                    "an item type a-z or A-Z",
+                ));
+            }
+        }
+
         return Ok(Rucksack {
             items: String::from(str),
         });
     }
 }
 
-fn priority(value: char) -> u32 {
+/// The priority of an item type: a-z are 1 to 26, A-Z are 27 to 52.
+pub fn priority(value: char) -> Option<u32> {
     let integer = value as u32;
 
-    if integer >= 65 && integer <= 90 {
-        return integer - 64 + 26;
+    if (65..=90).contains(&integer) {
+        return Some(integer - 64 + 26);
     }
 
-    if integer >= 97 && integer <= 122 {
-        return integer - 96;
+    if (97..=122).contains(&integer) {
+        return Some(integer - 96);
     }
 
-    panic!("crap");
+    return None;
 }
 
-fn part1(filename: &str) -> u32 {
-    let rucksacks: Vec<Rucksack> = read_lines(filename)
-        .unwrap()
-        .map(|x| x.unwrap())
-        .map(|x| Rucksack { items: x })
-        .collect();
+pub fn parse(input: &str) -> Result<Vec<Rucksack>, ParseError> {
+    return Input::from_text(input).parse_lines();
+}
 
+pub fn part1(rucksacks: &[Rucksack]) -> u32 {
     let mut sum = 0;
     for rucksack in rucksacks {
         for c in rucksack.items_in_both_compartments() {
-            sum += priority(c);
+            sum += priority(c).expect("rucksacks only hold item types");
         }
     }
 
@@ -83,13 +115,7 @@ fn intersection(a: String, b: String, c: String) -> HashSet<char> {
     return intersection;
 }
 
-fn part2(filename: &str) -> u32 {
-    let rucksacks: Vec<Rucksack> = read_lines(filename)
-        .unwrap()
-        .map(|x| x.unwrap())
-        .map(|x| Rucksack { items: x })
-        .collect();
-
+pub fn part2(rucksacks: &[Rucksack]) -> u32 {
     let mut sum = 0;
     for group in 0..(rucksacks.len() / 3) {
         let group_start = group * 3;
@@ -101,7 +127,7 @@ fn part2(filename: &str) -> u32 {
         );
 
         for ch in intersection {
-            sum += priority(ch);
+            sum += priority(ch).expect("rucksacks only hold item types");
         }
     }
 
@@ -111,6 +137,7 @@ fn part2(filename: &str) -> u32 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::io_utils::parse_file;
 
     #[test]
     fn rucksack_compartments_are_correct() {
@@ -136,30 +163,30 @@ mod tests {
 
     #[test]
     fn priority_is_correct() {
-        println!("{}", 'a' as u32);
-        assert_eq!(1, priority('a'));
-        assert_eq!(26, priority('z'));
-        assert_eq!(27, priority('A'));
-        assert_eq!(52, priority('Z'));
+        assert_eq!(Some(1), priority('a'));
+        assert_eq!(Some(26), priority('z'));
+        assert_eq!(Some(27), priority('A'));
+        assert_eq!(Some(52), priority('Z'));
+        assert_eq!(None, priority('1'));
     }
 
     #[test]
-    fn part1_example_is_correct() {
-        assert_eq!(157, part1("./data/day3_example.txt"));
-    }
+    fn parse_rejects_invalid_item() {
+        let error = parse("vJrwpWtwJgWr\nabc-de\n").err().unwrap();
 
-    #[test]
-    fn part1_is_correct() {
-        assert_eq!(8053, part1("./data/day3.txt"));
+        assert_eq!(
+            ParseError::unexpected_token("-", 4, "an item type a-z or A-Z").at_line(2),
+            error
+        );
     }
 
     #[test]
-    fn part2_example_is_correct() {
-        assert_eq!(70, part2("./data/day3_example.txt"));
+    fn part1_example_is_correct() {
+        assert_eq!(157, part1(&parse_file("./data/day3_example.txt", parse)));
     }
 
     #[test]
-    fn part2_is_correct() {
-        assert_eq!(2425, part2("./data/day3.txt"));
+    fn part2_example_is_correct() {
+        assert_eq!(70, part2(&parse_file("./data/day3_example.txt", parse)));
     }
 }
@@ -1,67 +1,141 @@
-use crate::io_utils::read_lines;
+use crate::error::{column_of, parse_number, ParseError};
+use crate::io_utils::Input;
+use crate::solution::{Answer, Solution};
 use std::cmp;
 use std::str::FromStr;
 
+pub struct Day4;
+
+impl Solution for Day4 {
+    const DAY: u8 = 4;
+    const TITLE: &'static str = "Camp Cleanup";
+
+    type Input = Vec<InputLine>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        return parse(input);
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer, String> {
+        return Ok(part1(input).into());
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer, String> {
+        return Ok(part2(input).into());
+    }
+}
+
 #[derive(Debug, PartialEq)]
-struct Range {
+pub struct Range {
     start: u32,
     end: u32,
 }
 
 impl Range {
-    fn contains(&self, other: &Range) -> bool {
+    pub fn new(start: u32, end: u32) -> Range {
+        return Range { start, end };
+    }
+
+    pub fn start(&self) -> u32 {
+        return self.start;
+    }
+
+    pub fn end(&self) -> u32 {
+        return self.end;
+    }
+
+    pub fn contains(&self, other: &Range) -> bool {
         return self.start <= other.start && self.end >= other.end;
     }
 
-    fn overlaps(&self, other: &Range) -> bool {
+    pub fn overlaps(&self, other: &Range) -> bool {
         return cmp::max(self.start, other.start) <= cmp::min(self.end, other.end);
     }
 }
 
-struct InputLine {
+pub struct InputLine {
     first_range: Range,
     second_range: Range,
 }
 
-#[derive(Debug)]
-struct InputLineParseError {}
+impl InputLine {
+    /// The section ranges of the two elves of the pair.
+    pub fn ranges(&self) -> (&Range, &Range) {
+        return (&self.first_range, &self.second_range);
+    }
+}
+
 impl FromStr for InputLine {
-    type Err = InputLineParseError;
+    type Err = ParseError;
 
     fn from_str(str: &str) -> Result<Self, Self::Err> {
         let split: Vec<&str> = str.trim().split(",").collect();
 
+        if split.len() < 2 {
+            return Err(ParseError::missing_field(
+                "second range",
+                str,
+                str.len() + 1,
+            ));
+        }
+
+        if split.len() > 2 {
+            return Err(ParseError::unexpected_token(
+                split[2],
+                column_of(str, split[2]),
+                "end of line",
+            ));
+        }
+
+        let parse_range = |part: &str| {
+            return part
+                .parse::<Range>()
+                .map_err(|e| e.offset_columns(column_of(str, part) - 1));
+        };
+
         return Ok(InputLine {
-            first_range: split[0].parse().unwrap(),
-            second_range: split[1].parse().unwrap(),
+            first_range: parse_range(split[0])?,
+            second_range: parse_range(split[1])?,
         });
     }
 }
 
-#[derive(Debug)]
-struct RangeParseError {}
 impl FromStr for Range {
-    type Err = RangeParseError;
+    type Err = ParseError;
 
     fn from_str(str: &str) -> Result<Self, Self::Err> {
         let split: Vec<&str> = str.trim().split("-").collect();
 
-        let start: u32 = split[0].parse().unwrap();
-        let end: u32 = split[1].parse().unwrap();
+        if split.len() < 2 {
+            return Err(ParseError::missing_field(
+                "end of range",
+                str,
+                str.len() + 1,
+            ));
+        }
+
+        if split.len() > 2 {
+            return Err(ParseError::unexpected_token(
+                split[2],
+                column_of(str, split[2]),
+                "end of range",
+            ));
+        }
+
+        let start: u32 = parse_number(str, split[0])?;
+        let end: u32 = parse_number(str, split[1])?;
 
         return Ok(Range { start, end });
     }
 }
 
-fn part1(filename: &str) -> u32 {
-    let input_lines: Vec<InputLine> = read_lines(filename)
-        .unwrap()
-        .map(|x| x.unwrap())
-        .map(|x| x.parse().unwrap())
-        .collect();
+pub fn parse(input: &str) -> Result<Vec<InputLine>, ParseError> {
+    return Input::from_text(input).parse_lines();
+}
 
+pub fn part1(input_lines: &[InputLine]) -> u32 {
     let contain_count: usize = input_lines
-        .into_iter()
+        .iter()
         .filter(|x| {
             x.first_range.contains(&x.second_range) || x.second_range.contains(&x.first_range)
         })
@@ -70,15 +144,9 @@ fn part1(filename: &str) -> u32 {
     return contain_count as u32;
 }
 
-fn part2(filename: &str) -> u32 {
-    let input_lines: Vec<InputLine> = read_lines(filename)
-        .unwrap()
-        .map(|x| x.unwrap())
-        .map(|x| x.parse().unwrap())
-        .collect();
-
+pub fn part2(input_lines: &[InputLine]) -> u32 {
     let overlap_count: usize = input_lines
-        .into_iter()
+        .iter()
         .filter(|x| x.first_range.overlaps(&x.second_range))
         .count();
 
@@ -88,6 +156,7 @@ fn part2(filename: &str) -> u32 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::io_utils::parse_file;
 
     #[test]
     fn range_parsing_is_correct() {
@@ -105,30 +174,33 @@ mod tests {
     }
 
     #[test]
-    fn part1_example_is_correct() {
-        let result = part1("./data/day4_example.txt");
+    fn input_line_parsing_reports_column() {
+        let result = "2-4,6-x".parse::<InputLine>();
 
-        assert_eq!(2, result);
+        assert_eq!(Some(ParseError::bad_number("x", 7)), result.err());
     }
 
     #[test]
-    fn part1_is_correct() {
-        let result = part1("./data/day4.txt");
+    fn parse_reports_missing_range() {
+        let error = parse("2-4,6-8\n2-3\n").err().unwrap();
 
-        assert_eq!(588, result);
+        assert_eq!(
+            ParseError::missing_field("second range", "2-3", 4).at_line(2),
+            error
+        );
     }
 
     #[test]
-    fn part2_example_is_correct() {
-        let result = part2("./data/day4_example.txt");
+    fn part1_example_is_correct() {
+        let result = part1(&parse_file("./data/day4_example.txt", parse));
 
-        assert_eq!(4, result);
+        assert_eq!(2, result);
     }
 
     #[test]
-    fn part2_is_correct() {
-        let result = part2("./data/day4.txt");
+    fn part2_example_is_correct() {
+        let result = part2(&parse_file("./data/day4_example.txt", parse));
 
-        assert_eq!(911, result);
+        assert_eq!(4, result);
     }
 }
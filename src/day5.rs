@@ -1,44 +1,108 @@
-use crate::io_utils::read_lines;
+use crate::error::{column_of, parse_number, ParseError};
+use crate::io_utils::Input;
+use crate::solution::{Answer, Solution};
+use std::cmp;
 use std::str::FromStr;
 
-#[derive(Debug)]
-struct CargoBay {
+pub struct Day5;
+
+impl Solution for Day5 {
+    const DAY: u8 = 5;
+    const TITLE: &'static str = "Supply Stacks";
+
+    type Input = Procedure;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        return parse(input);
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer, String> {
+        return part1(input).map(Answer::from);
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer, String> {
+        return part2(input).map(Answer::from);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CargoBay {
     stacks: Vec<Vec<char>>,
 }
 
 impl CargoBay {
-    pub fn move_item(&mut self, from: usize, to: usize) {
-        let item = self.stacks[from].pop().unwrap();
+    /// The crates of every stack, bottom first.
+    pub fn stacks(&self) -> &[Vec<char>] {
+        return &self.stacks;
+    }
+
+    /// Moves the top crate of stack `from` onto stack `to`, indexed from 0. Returns false,
+    /// moving nothing, when `from` is empty.
+    pub fn move_item(&mut self, from: usize, to: usize) -> bool {
+        let item = match self.stacks[from].pop() {
+            Some(item) => item,
+            None => return false,
+        };
 
         self.stacks[to].push(item);
+
+        return true;
     }
 
-    pub fn handle_move(&mut self, cargo_move: &CargoMove) {
-        for i in 0..cargo_move.quantity {
-            self.move_item(cargo_move.from - 1, cargo_move.to - 1);
+    /// Checks that both stacks of `cargo_move` exist and that it takes no more crates than
+    /// there are.
+    fn check_move(&self, cargo_move: &CargoMove) -> Result<(), String> {
+        for stack in [cargo_move.from, cargo_move.to] {
+            if stack == 0 || stack > self.stacks.len() {
+                return Err(format!(
+                    "there is no stack {}, only 1 to {}",
+                    stack,
+                    self.stacks.len()
+                ));
+            }
+        }
+
+        let crates = self.stacks[cargo_move.from - 1].len();
+        if cargo_move.quantity > crates {
+            return Err(format!(
+                "cannot move {} crates from stack {}, which has {}",
+                cargo_move.quantity, cargo_move.from, crates
+            ));
         }
+
+        return Ok(());
     }
 
-    pub fn handle_move_part2(&mut self, cargo_move: &CargoMove) {
-        let mut items: Vec<char> = vec![];
+    pub fn handle_move(&mut self, cargo_move: &CargoMove) -> Result<(), String> {
+        self.check_move(cargo_move)?;
 
         for _i in 0..cargo_move.quantity {
-            items.push(self.stacks[cargo_move.from - 1].pop().unwrap());
+            self.move_item(cargo_move.from - 1, cargo_move.to - 1);
         }
 
-        for item in items.into_iter().rev() {
-            self.stacks[cargo_move.to - 1].push(item);
-        }
+        return Ok(());
+    }
+
+    pub fn handle_move_part2(&mut self, cargo_move: &CargoMove) -> Result<(), String> {
+        self.check_move(cargo_move)?;
+
+        let from = &mut self.stacks[cargo_move.from - 1];
+        let items = from.split_off(from.len() - cargo_move.quantity);
+
+        self.stacks[cargo_move.to - 1].extend(items);
+
+        return Ok(());
     }
 
-    fn to_str(&self) -> String {
+    /// The crate on top of each non-empty stack, left to right.
+    pub fn top_crates(&self) -> String {
         let mut str = String::new();
 
         let cloned_stacks = self.stacks.clone();
 
         for stack in cloned_stacks {
-            if stack.len() != 0 {
-                str.push(stack.last().unwrap().clone());
+            if !stack.is_empty() {
+                str.push(*stack.last().unwrap());
             }
         }
 
@@ -46,32 +110,54 @@ impl CargoBay {
     }
 }
 
-#[derive(Debug)]
-struct CargoBayParseError;
-
 impl FromStr for CargoBay {
-    type Err = CargoBayParseError;
+    type Err = ParseError;
 
     fn from_str(str: &str) -> Result<Self, Self::Err> {
-        let mut stacks: Vec<Vec<char>> = vec![vec![]; 10];
+        let lines: Vec<&str> = str.lines().collect();
+
+        // The row of stack numbers tells how many stacks there are, even when some start empty
+        let mut stacks: Vec<Vec<char>> = match lines.last() {
+            Some(line) if !line.trim().starts_with("[") => {
+                vec![vec![]; line.split_whitespace().count()]
+            }
+            _ => vec![],
+        };
 
-        for line in str.lines().rev() {
+        for (i, line) in lines.iter().enumerate().rev() {
             if !line.trim().starts_with("[") {
                 continue;
             }
 
-            for stack_index in 0..(line.len() / 4) {
-                let stack = &mut stacks[stack_index];
+            // The last crate on a line is not followed by a space
+            for stack_index in 0..((line.len() + 1) / 4) {
+                if stacks.len() <= stack_index {
+                    stacks.push(vec![]);
+                }
+
+                let end = cmp::min((stack_index * 4) + 4, line.len());
+                let cell = line.get(stack_index * 4..end).unwrap_or(line).trim();
 
-                let value: Option<char> = line[stack_index * 4..((stack_index * 4) + 4)]
-                    .trim()
+                if cell.is_empty() {
+                    continue;
+                }
+
+                let value: Option<char> = cell
                     .strip_prefix("[")
                     .and_then(|x| x.strip_suffix("]"))
-                    .and_then(|x| x.chars().nth(0));
+                    .filter(|x| x.chars().count() == 1)
+                    .and_then(|x| x.chars().next());
 
                 match value {
-                    Some(some_value) => stack.push(some_value),
-                    None => {}
+                    Some(some_value) => stacks[stack_index].push(some_value),
+                    None => {
+                        return Err(ParseError::unexpected_token(
+                            cell,
+                            column_of(line, cell),
+                            "a crate like [A]",
+                        )
+                        .at_line(i + 1))
+                    }
                 }
             }
         }
@@ -81,124 +167,298 @@ impl FromStr for CargoBay {
 }
 
 #[derive(Debug)]
-struct CargoMove {
+pub struct CargoMove {
     quantity: usize,
     from: usize,
     to: usize,
 }
 
-#[derive(Debug)]
-struct CargoMoveParseError;
+impl CargoMove {
+    /// A move of `quantity` crates between stacks numbered from 1, as in the input.
+    pub fn new(quantity: usize, from: usize, to: usize) -> CargoMove {
+        return CargoMove { quantity, from, to };
+    }
+}
+
 impl FromStr for CargoMove {
-    type Err = CargoMoveParseError;
+    type Err = ParseError;
 
     fn from_str(str: &str) -> Result<Self, Self::Err> {
-        let numbers: Vec<usize> = str
-            .split(" ")
-            .map(|x| x.parse())
-            .filter(|x| x.is_ok())
-            .map(|x| x.unwrap())
-            .collect();
-
-        return Ok(CargoMove {
-            quantity: numbers[0],
-            from: numbers[1],
-            to: numbers[2],
-        });
+        let split: Vec<&str> = str.split(" ").collect();
+
+        let field = |index: usize, name: &'static str| {
+            return split
+                .get(index)
+                .copied()
+                .filter(|x| !x.is_empty())
+                .ok_or_else(|| ParseError::missing_field(name, str, str.len() + 1));
+        };
+
+        let keyword = |index: usize, keyword: &'static str| {
+            let token = field(index, keyword)?;
+
+            if token != keyword {
+                return Err(ParseError::unexpected_token(
+                    token,
+                    column_of(str, token),
+                    keyword,
+                ));
+            }
+
+            return Ok(());
+        };
+
+        let stack = |index: usize, name: &'static str| {
+            let token = field(index, name)?;
+            let number: usize = parse_number(str, token)?;
+
+            if number == 0 {
+                return Err(ParseError::unexpected_token(
+                    token,
+                    column_of(str, token),
+                    "a stack number from 1",
+                ));
+            }
+
+            return Ok(number);
+        };
+
+        keyword(0, "move")?;
+        let quantity: usize = parse_number(str, field(1, "quantity")?)?;
+        keyword(2, "from")?;
+        let from = stack(3, "source stack")?;
+        keyword(4, "to")?;
+        let to = stack(5, "target stack")?;
+
+        if let Some(token) = split.get(6) {
+            return Err(ParseError::unexpected_token(
+                token,
+                column_of(str, token),
+                "end of line",
+            ));
+        }
+
+        return Ok(CargoMove { quantity, from, to });
     }
 }
 
-fn part1(filename: &str) -> String {
-    let lines: Vec<String> = read_lines(filename).unwrap().map(|x| x.unwrap()).collect();
-    let content = lines.join("\n");
+/// The starting arrangement of the crates and the rearrangement procedure to apply to it.
+pub struct Procedure {
+    cargo_bay: CargoBay,
+    cargo_moves: Vec<CargoMove>,
+}
+
+impl Procedure {
+    pub fn cargo_bay(&self) -> &CargoBay {
+        return &self.cargo_bay;
+    }
 
-    let split: Vec<&str> = content.split("\n\n").collect();
+    pub fn cargo_moves(&self) -> &[CargoMove] {
+        return &self.cargo_moves;
+    }
+}
 
-    let mut cargo_bay: CargoBay = split[0].parse().unwrap();
+pub fn parse(input: &str) -> Result<Procedure, ParseError> {
+    let input = Input::from_text(input);
+    let paragraphs = input.paragraphs();
 
-    println!("{:?}", cargo_bay);
+    let drawing = match paragraphs.first() {
+        Some(paragraph) => paragraph,
+        None => return Err(ParseError::missing_field("starting stacks", "", 1)),
+    };
 
-    let cargo_moves: Vec<CargoMove> = split[1]
-        .split("\n")
-        .map(|x| x.parse())
-        .map(|x| x.unwrap())
-        .collect();
+    let procedure = match paragraphs.get(1) {
+        Some(paragraph) => paragraph,
+        None => {
+            return Err(ParseError::missing_field("rearrangement procedure", "", 1)
+                .at_line(drawing.first_line + drawing.lines().count()))
+        }
+    };
 
-    for cargo_move in cargo_moves {
-        cargo_bay.handle_move(&cargo_move);
-        println!("DONE {:?}", cargo_move);
+    if let Some(paragraph) = paragraphs.get(2) {
+        return Err(
+            ParseError::unexpected_token(paragraph.text, 1, "end of input")
+                .at_line(paragraph.first_line),
+        );
     }
 
-    println!("{:?}", cargo_bay);
+    let cargo_bay: CargoBay = drawing.text.parse()?;
+    let cargo_moves: Vec<CargoMove> = procedure.parse_lines()?;
+
+    for (i, (line, cargo_move)) in procedure.lines().zip(&cargo_moves).enumerate() {
+        // The stack numbers are the 4th and 6th words, as checked by `CargoMove::from_str`
+        let words: Vec<&str> = line.split(' ').collect();
+
+        for (token, stack) in [(words[3], cargo_move.from), (words[5], cargo_move.to)] {
+            if stack > cargo_bay.stacks.len() {
+                return Err(ParseError::unexpected_token(
+                    token,
+                    column_of(line, token),
+                    "a stack number in the drawing",
+                )
+                .at_line(procedure.first_line + i));
+            }
+        }
+    }
 
-    return cargo_bay.to_str();
+    return Ok(Procedure {
+        cargo_bay,
+        cargo_moves,
+    });
 }
 
-fn part2(filename: &str) -> String {
-    let lines: Vec<String> = read_lines(filename).unwrap().map(|x| x.unwrap()).collect();
-    let content = lines.join("\n");
+pub fn part1(procedure: &Procedure) -> Result<String, String> {
+    let mut cargo_bay = procedure.cargo_bay.clone();
 
-    let split: Vec<&str> = content.split("\n\n").collect();
+    log::debug!("start: {:?}", cargo_bay);
 
-    let mut cargo_bay: CargoBay = split[0].parse().unwrap();
+    for (i, cargo_move) in procedure.cargo_moves.iter().enumerate() {
+        cargo_bay
+            .handle_move(cargo_move)
+            .map_err(|e| format!("move {}: {}", i + 1, e))?;
+        log::trace!("done: {:?}", cargo_move);
+    }
+
+    log::debug!("end: {:?}", cargo_bay);
 
-    println!("{:?}", cargo_bay);
+    return Ok(cargo_bay.top_crates());
+}
 
-    let cargo_moves: Vec<CargoMove> = split[1]
-        .split("\n")
-        .map(|x| x.parse())
-        .map(|x| x.unwrap())
-        .collect();
+pub fn part2(procedure: &Procedure) -> Result<String, String> {
+    let mut cargo_bay = procedure.cargo_bay.clone();
 
-    for cargo_move in cargo_moves {
-        println!("START {:?}", cargo_move);
-        cargo_bay.handle_move_part2(&cargo_move);
-        println!("DONE {:?}", cargo_move);
+    log::debug!("start: {:?}", cargo_bay);
+
+    for (i, cargo_move) in procedure.cargo_moves.iter().enumerate() {
+        cargo_bay
+            .handle_move_part2(cargo_move)
+            .map_err(|e| format!("move {}: {}", i + 1, e))?;
+        log::trace!("done: {:?}", cargo_move);
     }
 
-    println!("{:?}", cargo_bay);
+    log::debug!("end: {:?}", cargo_bay);
 
-    return cargo_bay.to_str();
+    return Ok(cargo_bay.top_crates());
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::io_utils::parse_file;
 
     #[test]
-    fn parse_move_is_correct() {
-        let cargo_move: CargoMove = "move 1 from 2 to 1".parse().unwrap();
+    fn parse_is_correct() {
+        let procedure =
+            parse("    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\nmove 1 from 2 to 1\n")
+                .unwrap();
+
+        assert_eq!(vec!['Z', 'N'], procedure.cargo_bay.stacks[0]);
+        assert_eq!(vec!['M', 'C', 'D'], procedure.cargo_bay.stacks[1]);
+        assert_eq!(vec!['P'], procedure.cargo_bay.stacks[2]);
+        assert_eq!(1, procedure.cargo_moves.len());
+        assert_eq!(Ok(String::from("DCP")), part1(&procedure));
+    }
 
-        assert_eq!(1, cargo_move.quantity);
-        assert_eq!(2, cargo_move.from);
-        assert_eq!(1, cargo_move.to);
+    #[test]
+    fn parse_without_procedure_fails() {
+        let error = parse("[Z] [M] [P]\n 1   2   3 ").err().unwrap();
+
+        assert_eq!(
+            ParseError::missing_field("rearrangement procedure", "", 1).at_line(3),
+            error
+        );
     }
 
     #[test]
-    fn part1_example_is_correct() {
-        let result = part1("./data/day5_example.txt");
+    fn parse_reports_invalid_crate() {
+        let error = parse("[Z] (M) [P]\n 1   2   3 \n\nmove 1 from 2 to 1")
+            .err()
+            .unwrap();
+
+        assert_eq!(
+            ParseError::unexpected_token("(M)", 5, "a crate like [A]"),
+            error
+        );
+    }
 
-        assert_eq!("CMZ", result);
+    #[test]
+    fn parse_reports_invalid_move() {
+        let error = parse("[Z]\n 1 \n\nmove 1 from 1 to 1\nmove 1 frm 1 to 1")
+            .err()
+            .unwrap();
+
+        assert_eq!(
+            ParseError::unexpected_token("frm", 8, "from").at_line(5),
+            error
+        );
     }
 
     #[test]
-    fn part1_is_correct() {
-        let result = part1("./data/day5.txt");
+    fn parse_rejects_stack_missing_from_drawing() {
+        let error = parse("[Z]\n 1 \n\nmove 1 from 1 to 1\nmove 1 from 1 to 5")
+            .err()
+            .unwrap();
+
+        assert_eq!(
+            ParseError::unexpected_token("5", 18, "a stack number in the drawing").at_line(5),
+            error
+        );
+    }
 
-        assert_eq!("RTGWZTHLD", result);
+    #[test]
+    fn moving_too_many_crates_is_an_error() {
+        let procedure = parse("[Z]\n 1 \n\nmove 3 from 1 to 1").unwrap();
+
+        assert_eq!(
+            Err(String::from(
+                "move 1: cannot move 3 crates from stack 1, which has 1"
+            )),
+            part2(&procedure)
+        );
     }
 
     #[test]
-    fn part2_example_is_correct() {
-        let result = part2("./data/day5_example.txt");
+    fn handle_move_rejects_missing_stacks() {
+        let mut cargo_bay: CargoBay = "[Z]\n 1   2 ".parse().unwrap();
+
+        assert_eq!(
+            Err(String::from("there is no stack 0, only 1 to 2")),
+            cargo_bay.handle_move(&CargoMove::new(1, 0, 1))
+        );
+        assert!(cargo_bay
+            .handle_move_part2(&CargoMove::new(1, 1, 3))
+            .is_err());
+        assert_eq!(vec![vec!['Z'], vec![]], cargo_bay.stacks);
+    }
+
+    #[test]
+    fn move_item_from_empty_stack_moves_nothing() {
+        let mut cargo_bay: CargoBay = "[Z]\n 1   2 ".parse().unwrap();
+
+        assert!(!cargo_bay.move_item(1, 0));
+        assert_eq!(vec![vec!['Z'], vec![]], cargo_bay.stacks);
+    }
+
+    #[test]
+    fn parse_move_is_correct() {
+        let cargo_move: CargoMove = "move 1 from 2 to 1".parse().unwrap();
 
-        assert_eq!("MCD", result);
+        assert_eq!(1, cargo_move.quantity);
+        assert_eq!(2, cargo_move.from);
+        assert_eq!(1, cargo_move.to);
     }
 
     #[test]
-    fn part2_is_correct() {
-        let result = part2("./data/day5.txt");
+    fn part1_example_is_correct() {
+        let result = part1(&parse_file("./data/day5_example.txt", parse));
+
+        assert_eq!(Ok(String::from("CMZ")), result);
+    }
+
+    #[test]
+    fn part2_example_is_correct() {
+        let result = part2(&parse_file("./data/day5_example.txt", parse));
 
-        assert_eq!("STHGRZZFR", result);
+        assert_eq!(Ok(String::from("MCD")), result);
     }
 }
@@ -1,81 +1,106 @@
+use crate::error::ParseError;
+use crate::solution::{Answer, Solution};
 use itertools::Itertools;
 
-fn find_start_of_packet_marker(signal: &str) -> Option<usize> {
-    for i in 3..signal.len() {
-        if signal[i - 3..(i + 1)].chars().unique().count() == 4 {
-            return Some(i);
-        }
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: u8 = 6;
+    const TITLE: &'static str = "Tuning Trouble";
+
+    type Input = String;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        return parse(input);
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer, String> {
+        return part1(input)
+            .map(Answer::from)
+            .ok_or_else(|| String::from("there is no start-of-packet marker"));
     }
 
-    return None;
+    fn part2(input: &Self::Input) -> Result<Answer, String> {
+        return part2(input)
+            .map(Answer::from)
+            .ok_or_else(|| String::from("there is no start-of-message marker"));
+    }
 }
 
-fn find_start_of_message_marker(signal: &str) -> Option<usize> {
-    for i in 13..signal.len() {
-        if signal[i - 13..(i + 1)].chars().unique().count() == 14 {
-            return Some(i);
-        }
+pub fn parse(input: &str) -> Result<String, ParseError> {
+    let signal = input.trim();
+
+    if signal.is_empty() {
+        return Err(ParseError::missing_field("datastream", input, 1));
     }
 
-    return None;
+    return Ok(String::from(signal));
 }
 
-fn part1(signal: &str) -> usize {
-    return match find_start_of_packet_marker(signal) {
-        Some(marker) => marker + 1,
-        None => panic!("We dorked it all up!"),
-    };
+/// The index of the last character of the first `length` distinct characters in a row.
+fn find_marker(signal: &str, length: usize) -> Option<usize> {
+    let chars: Vec<char> = signal.chars().collect();
+
+    return chars
+        .windows(length)
+        .position(|window| window.iter().unique().count() == length)
+        .map(|start| start + length - 1);
 }
 
-fn part2(signal: &str) -> usize {
-    return match find_start_of_message_marker(signal) {
-        Some(marker) => marker + 1,
-        None => panic!("We dorked it all up!"),
-    };
+fn find_start_of_packet_marker(signal: &str) -> Option<usize> {
+    return find_marker(signal, 4);
+}
+
+fn find_start_of_message_marker(signal: &str) -> Option<usize> {
+    return find_marker(signal, 14);
+}
+
+/// The number of characters read when the start-of-packet marker is complete, if there is one.
+pub fn part1(signal: &str) -> Option<usize> {
+    return find_start_of_packet_marker(signal).map(|marker| marker + 1);
+}
+
+/// The number of characters read when the start-of-message marker is complete, if there is one.
+pub fn part2(signal: &str) -> Option<usize> {
+    return find_start_of_message_marker(signal).map(|marker| marker + 1);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::io_utils::read_lines;
 
     #[test]
     fn part1_example_is_correct() {
-        assert_eq!(7, part1("mjqjpqmgbljsphdztnvjfqwrcgsmlb"));
-        assert_eq!(5, part1("bvwbjplbgvbhsrlpgdmjqwftvncz"));
-        assert_eq!(6, part1("nppdvjthqldpwncqszvftbrmjlhg"));
-        assert_eq!(10, part1("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"));
-        assert_eq!(11, part1("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"));
+        assert_eq!(Some(7), part1("mjqjpqmgbljsphdztnvjfqwrcgsmlb"));
+        assert_eq!(Some(5), part1("bvwbjplbgvbhsrlpgdmjqwftvncz"));
+        assert_eq!(Some(6), part1("nppdvjthqldpwncqszvftbrmjlhg"));
+        assert_eq!(Some(10), part1("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"));
+        assert_eq!(Some(11), part1("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"));
     }
 
     #[test]
-    fn part1_is_correct() {
-        let signal = read_lines("./data/day6.txt")
-            .unwrap()
-            .map(|x| x.unwrap())
-            .nth(0)
-            .unwrap();
-
-        assert_eq!(1876, part1(&signal[..]));
+    fn signal_without_message_marker_solves_part1_only() {
+        let signal = parse("abcd\n").unwrap();
+
+        assert_eq!(Some(4), part1(&signal));
+        assert_eq!(None, part2(&signal));
+        assert!(Day6::part2(&signal).is_err());
+        assert_eq!(None, part1("abc"));
     }
 
     #[test]
-    fn part2_example_is_correct() {
-        assert_eq!(19, part2("mjqjpqmgbljsphdztnvjfqwrcgsmlb"));
-        assert_eq!(23, part2("bvwbjplbgvbhsrlpgdmjqwftvncz"));
-        assert_eq!(23, part2("nppdvjthqldpwncqszvftbrmjlhg"));
-        assert_eq!(29, part2("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"));
-        assert_eq!(26, part2("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"));
+    fn markers_count_characters_not_bytes() {
+        assert_eq!(Some(5), part1("ééabc"));
+        assert_eq!(Some(4), part1("αβγδ"));
+        assert_eq!(None, part2("αβγδ"));
     }
 
     #[test]
-    fn part2_is_correct() {
-        let signal = read_lines("./data/day6.txt")
-            .unwrap()
-            .map(|x| x.unwrap())
-            .nth(0)
-            .unwrap();
-
-        assert_eq!(2202, part2(&signal[..]));
+    fn part2_example_is_correct() {
+        assert_eq!(Some(19), part2("mjqjpqmgbljsphdztnvjfqwrcgsmlb"));
+        assert_eq!(Some(23), part2("bvwbjplbgvbhsrlpgdmjqwftvncz"));
+        assert_eq!(Some(23), part2("nppdvjthqldpwncqszvftbrmjlhg"));
+        assert_eq!(Some(29), part2("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"));
+        assert_eq!(Some(26), part2("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"));
     }
 }
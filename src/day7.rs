@@ -0,0 +1,296 @@
+use crate::error::{column_of, parse_number, ParseError};
+use crate::io_utils::Input;
+use crate::solution::{Answer, Solution};
+
+pub struct Day7;
+
+impl Solution for Day7 {
+    const DAY: u8 = 7;
+    const TITLE: &'static str = "No Space Left On Device";
+
+    type Input = FileSystem;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        return parse(input);
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer, String> {
+        return Ok(part1(input).into());
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer, String> {
+        return Ok(part2(input).into());
+    }
+}
+
+const DISK_SIZE: u64 = 70_000_000;
+const SPACE_NEEDED: u64 = 30_000_000;
+
+#[derive(Debug)]
+pub struct Directory {
+    name: String,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    files: Vec<(String, u64)>,
+}
+
+impl Directory {
+    fn new(name: &str, parent: Option<usize>) -> Directory {
+        return Directory {
+            name: String::from(name),
+            parent,
+            children: vec![],
+            files: vec![],
+        };
+    }
+}
+
+/// The directory tree seen in the terminal output. Directories refer to each other by their
+/// index, and the root is always the first one.
+#[derive(Debug)]
+pub struct FileSystem {
+    directories: Vec<Directory>,
+}
+
+impl FileSystem {
+    const ROOT: usize = 0;
+
+    fn new() -> FileSystem {
+        return FileSystem {
+            directories: vec![Directory::new("/", None)],
+        };
+    }
+
+    fn child(&self, directory: usize, name: &str) -> Option<usize> {
+        return self.directories[directory]
+            .children
+            .iter()
+            .copied()
+            .find(|x| self.directories[*x].name == name);
+    }
+
+    /// Finds the directory `name` in `directory`, adding it if it has not been seen yet.
+    fn add_directory(&mut self, directory: usize, name: &str) -> usize {
+        if let Some(child) = self.child(directory, name) {
+            return child;
+        }
+
+        self.directories.push(Directory::new(name, Some(directory)));
+        let child = self.directories.len() - 1;
+        self.directories[directory].children.push(child);
+
+        return child;
+    }
+
+    /// Adds a file, ignoring files that were already listed.
+    fn add_file(&mut self, directory: usize, name: &str, size: u64) {
+        let files = &mut self.directories[directory].files;
+
+        if files.iter().all(|(x, _)| x != name) {
+            files.push((String::from(name), size));
+        }
+    }
+
+    /// The total size of all files.
+    pub fn total_size(&self) -> u64 {
+        return self.sizes()[FileSystem::ROOT];
+    }
+
+    /// The total size of every directory, including everything below it, by index.
+    pub fn sizes(&self) -> Vec<u64> {
+        let mut sizes: Vec<u64> = self
+            .directories
+            .iter()
+            .map(|x| x.files.iter().map(|(_, size)| size).sum())
+            .collect();
+
+        // Directories are always added after their parent, so children are summed up first
+        for (i, directory) in self.directories.iter().enumerate().rev() {
+            if let Some(parent) = directory.parent {
+                sizes[parent] += sizes[i];
+            }
+        }
+
+        return sizes;
+    }
+}
+
+pub fn parse(input: &str) -> Result<FileSystem, ParseError> {
+    let mut file_system = FileSystem::new();
+    let mut current = FileSystem::ROOT;
+    let mut listing = false;
+
+    for (i, line) in Input::from_text(input).lines().enumerate() {
+        let error = |e: ParseError| e.at_line(i + 1);
+        let split: Vec<&str> = line.split(' ').collect();
+
+        let field = |index: usize, name: &'static str| {
+            return split
+                .get(index)
+                .copied()
+                .filter(|x| !x.is_empty())
+                .ok_or_else(|| error(ParseError::missing_field(name, line, line.len() + 1)));
+        };
+
+        if let Some(token) = split.get(3) {
+            return Err(error(ParseError::unexpected_token(
+                token,
+                column_of(line, token),
+                "end of line",
+            )));
+        }
+
+        if split[0] == "$" {
+            let command = field(1, "command")?;
+            listing = false;
+
+            match command {
+                "cd" => {
+                    let name = field(2, "directory")?;
+
+                    current = match name {
+                        "/" => FileSystem::ROOT,
+                        ".." => match file_system.directories[current].parent {
+                            Some(parent) => parent,
+                            None => {
+                                return Err(error(ParseError::unexpected_token(
+                                    name,
+                                    column_of(line, name),
+                                    "a directory below /",
+                                )))
+                            }
+                        },
+                        _ => file_system.add_directory(current, name),
+                    };
+                }
+                "ls" => {
+                    if let Some(token) = split.get(2) {
+                        return Err(error(ParseError::unexpected_token(
+                            token,
+                            column_of(line, token),
+                            "end of line",
+                        )));
+                    }
+
+                    listing = true;
+                }
+                _ => {
+                    return Err(error(ParseError::unexpected_token(
+                        command,
+                        column_of(line, command),
+                        "cd or ls",
+                    )))
+                }
+            }
+
+            continue;
+        }
+
+        if !listing {
+            return Err(error(ParseError::unexpected_token(line, 1, "a command")));
+        }
+
+        let name = field(1, "name")?;
+
+        if let Some(token) = split.get(2) {
+            return Err(error(ParseError::unexpected_token(
+                token,
+                column_of(line, token),
+                "end of line",
+            )));
+        }
+
+        if split[0] == "dir" {
+            file_system.add_directory(current, name);
+        } else {
+            let size: u64 = parse_number(line, split[0]).map_err(error)?;
+            file_system.add_file(current, name, size);
+        }
+    }
+
+    return Ok(file_system);
+}
+
+pub fn part1(file_system: &FileSystem) -> u64 {
+    return file_system
+        .sizes()
+        .into_iter()
+        .filter(|x| *x <= 100_000)
+        .sum();
+}
+
+pub fn part2(file_system: &FileSystem) -> u64 {
+    let sizes = file_system.sizes();
+    let free = DISK_SIZE.saturating_sub(sizes[FileSystem::ROOT]);
+    let to_free = SPACE_NEEDED.saturating_sub(free);
+
+    return sizes
+        .into_iter()
+        .filter(|x| *x >= to_free)
+        .min()
+        .expect("deleting / always frees enough space");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io_utils::parse_file;
+
+    #[test]
+    fn sizes_are_correct() {
+        let file_system = parse_file("./data/day7_example.txt", parse);
+
+        let sizes = file_system.sizes();
+
+        assert_eq!(48_381_165, sizes[FileSystem::ROOT]);
+        assert_eq!(584, sizes[file_system.child(1, "e").unwrap()]);
+    }
+
+    #[test]
+    fn parse_ignores_repeated_listing() {
+        let file_system = parse("$ cd /\n$ ls\n10 a\n$ ls\n10 a\n").unwrap();
+
+        assert_eq!(vec![10], file_system.sizes());
+    }
+
+    #[test]
+    fn parse_reports_unknown_command() {
+        let error = parse("$ cd /\n$ rm a").err().unwrap();
+
+        assert_eq!(
+            ParseError::unexpected_token("rm", 3, "cd or ls").at_line(2),
+            error
+        );
+    }
+
+    #[test]
+    fn parse_reports_bad_size() {
+        let error = parse("$ ls\n12a b.txt").err().unwrap();
+
+        assert_eq!(ParseError::bad_number("12a", 1).at_line(2), error);
+    }
+
+    #[test]
+    fn parse_reports_cd_above_root() {
+        let error = parse("$ cd /\n$ cd ..").err().unwrap();
+
+        assert_eq!(
+            ParseError::unexpected_token("..", 6, "a directory below /").at_line(2),
+            error
+        );
+    }
+
+    #[test]
+    fn part1_example_is_correct() {
+        let result = part1(&parse_file("./data/day7_example.txt", parse));
+
+        assert_eq!(95437, result);
+    }
+
+    #[test]
+    fn part2_example_is_correct() {
+        let result = part2(&parse_file("./data/day7_example.txt", parse));
+
+        assert_eq!(24933642, result);
+    }
+}
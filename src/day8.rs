@@ -1,20 +1,42 @@
-#![allow(dead_code)]
+use crate::error::ParseError;
+use crate::io_utils::Input;
+use crate::solution::{Answer, Solution};
 
-use crate::io_utils::read_lines;
-#[derive(Debug)]
-struct Vec2 {
-    x: usize,
-    y: usize,
+pub struct Day8;
+
+impl Solution for Day8 {
+    const DAY: u8 = 8;
+    const TITLE: &'static str = "Treetop Tree House";
+
+    type Input = Grid;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        return parse(input);
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer, String> {
+        return Ok(part1(input).into());
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer, String> {
+        return Ok(part2(input).into());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Vec2 {
+    pub x: usize,
+    pub y: usize,
 }
 
 #[derive(Debug)]
-struct Grid {
+pub struct Grid {
     content: Vec<u32>,
     width: usize,
 }
 
 impl Grid {
-    fn get(&self, coordinate: &Vec2) -> Option<u32> {
+    pub fn get(&self, coordinate: &Vec2) -> Option<u32> {
         return self.get_by_index(coordinate.y * self.width + coordinate.x);
     }
 
@@ -25,15 +47,15 @@ impl Grid {
         return None;
     }
 
-    fn width(&self) -> usize {
+    pub fn width(&self) -> usize {
         return self.width;
     }
 
-    fn height(&self) -> usize {
+    pub fn height(&self) -> usize {
         return self.content.len() / self.width;
     }
 
-    fn scenic_score(&self, coordinate: &Vec2) -> usize {
+    pub fn scenic_score(&self, coordinate: &Vec2) -> usize {
         let value = self.get(coordinate).unwrap();
 
         let mut up_score = 0;
@@ -79,7 +101,7 @@ impl Grid {
         return up_score * right_score * under_score * left_score;
     }
 
-    fn is_visible(&self, coordinate: &Vec2) -> bool {
+    pub fn is_visible(&self, coordinate: &Vec2) -> bool {
         let value = self.get(coordinate).unwrap();
 
         if self.above(coordinate).iter().all(|x| x < &value) {
@@ -141,7 +163,7 @@ impl Grid {
         return values;
     }
 
-    fn coordinates(&self) -> Vec<Vec2> {
+    pub fn coordinates(&self) -> Vec<Vec2> {
         let mut coordinates = vec![];
         for y in 0..self.height() {
             for x in 0..self.width() {
@@ -153,21 +175,16 @@ impl Grid {
     }
 }
 
-fn part1(filename: &str) -> usize {
-    let lines: Vec<String> = read_lines(filename).unwrap().map(|x| x.unwrap()).collect();
-    let width = lines[0].len();
+pub fn parse(input: &str) -> Result<Grid, ParseError> {
+    let rows = Input::from_text(input).grid_of_digits()?;
 
-    let numbers: Vec<u32> = lines
-        .iter()
-        .flat_map(|line| line.chars())
-        .map(|x| x.to_digit(10).unwrap())
-        .collect();
-
-    let grid = Grid {
-        content: numbers,
-        width,
-    };
+    return Ok(Grid {
+        width: rows[0].len(),
+        content: rows.concat(),
+    });
+}
 
+pub fn part1(grid: &Grid) -> usize {
     let visible_count = grid
         .coordinates()
         .iter()
@@ -177,21 +194,7 @@ fn part1(filename: &str) -> usize {
     return visible_count;
 }
 
-fn part2(filename: &str) -> usize {
-    let lines: Vec<String> = read_lines(filename).unwrap().map(|x| x.unwrap()).collect();
-    let width = lines[0].len();
-
-    let numbers: Vec<u32> = lines
-        .iter()
-        .flat_map(|line| line.chars())
-        .map(|x| x.to_digit(10).unwrap())
-        .collect();
-
-    let grid = Grid {
-        content: numbers,
-        width,
-    };
-
+pub fn part2(grid: &Grid) -> usize {
     let largest_scenic_score = grid
         .coordinates()
         .iter()
@@ -205,32 +208,41 @@ fn part2(filename: &str) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::io_utils::parse_file;
 
     #[test]
-    fn part1_example_is_correct() {
-        let result = part1("./data/day8_example.txt");
+    fn parse_is_correct() {
+        let grid = parse("303\n255\n").unwrap();
 
-        assert_eq!(21, result);
+        assert_eq!(3, grid.width());
+        assert_eq!(2, grid.height());
+        assert_eq!(Some(5), grid.get(&Vec2 { x: 1, y: 1 }));
     }
 
     #[test]
-    fn part1_is_correct() {
-        let result = part1("./data/day8.txt");
-
-        assert_eq!(1672, result);
+    fn parse_rejects_invalid_height() {
+        assert_eq!(
+            Err(ParseError::unexpected_token("x", 2, "a digit").at_line(2)),
+            parse("303\n2x5\n").map(|_| ())
+        );
+        assert_eq!(
+            Err(ParseError::missing_field("digit", "25", 3).at_line(2)),
+            parse("303\n25\n").map(|_| ())
+        );
+        assert!(parse("").is_err());
     }
 
     #[test]
-    fn part2_example_is_correct() {
-        let result = part2("./data/day8_example.txt");
+    fn part1_example_is_correct() {
+        let result = part1(&parse_file("./data/day8_example.txt", parse));
 
-        assert_eq!(8, result);
+        assert_eq!(21, result);
     }
 
     #[test]
-    fn part2_is_correct() {
-        let result = part2("./data/day8.txt");
+    fn part2_example_is_correct() {
+        let result = part2(&parse_file("./data/day8_example.txt", parse));
 
-        assert_eq!(327180, result);
+        assert_eq!(8, result);
     }
 }
@@ -1,70 +1,89 @@
-use crate::io_utils::read_lines;
-use itertools::Itertools;
+use crate::error::{column_of, parse_number, ParseError};
+use crate::io_utils::Input;
+use crate::solution::{Answer, Solution};
+use std::collections::HashSet;
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
-struct Vec2 {
-    x: i32,
-    y: i32,
-}
+mod follow;
+mod render;
 
-struct Knot<'a> {
-    position: Vec2,
-    next: &'a mut Option<&'a mut Knot<'a>>,
-}
+pub use follow::{Chebyshev, FollowRule, Manhattan, Orthogonal, Slack};
+pub use render::Trail;
 
-impl<'a> Knot<'a> {
-    fn tail(&mut self) -> &Knot<'a> {
-        return match self.next {
-            Some(n) => n.tail(),
-            None => self,
-        };
-    }
+pub struct Day9;
 
-    fn step(&mut self, step: &Vec2) {
-        self.position = self.position.plus(step);
+impl Solution for Day9 {
+    const DAY: u8 = 9;
+    const TITLE: &'static str = "Rope Bridge";
 
-        match self.next {
-            Some(n) => {
-                let difference = self.position.minus(&n.position);
+    type Input = Vec<Move>;
 
-                if difference.x.abs() >= 2 || difference.y.abs() >= 2 {
-                    let direction = &difference.direction();
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        return parse(input);
+    }
 
-                    n.step(direction);
-                }
-            }
-            None => {}
-        }
+    fn part1(input: &Self::Input) -> Result<Answer, String> {
+        return Ok(part1(input).into());
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer, String> {
+        return Ok(part2(input).into());
     }
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Vec2 {
+    pub x: i32,
+    pub y: i32,
+}
+
 static UP: Vec2 = Vec2 { x: 0, y: -1 };
 static RIGHT: Vec2 = Vec2 { x: 1, y: 0 };
 static DOWN: Vec2 = Vec2 { x: 0, y: 1 };
 static LEFT: Vec2 = Vec2 { x: -1, y: 0 };
 
+/// One line of the input: the head moves `count` times in `direction`, which is one cell
+/// along an axis or a diagonal.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Move {
+    pub direction: Vec2,
+    pub count: u32,
+}
+
+impl Move {
+    pub fn new(direction: Vec2, count: u32) -> Move {
+        return Move { direction, count };
+    }
+
+    /// The single steps of the move, made as they are needed.
+    pub fn steps(&self) -> impl Iterator<Item = Vec2> {
+        return std::iter::repeat_n(self.direction, self.count as usize);
+    }
+}
+
+/// Every single step of `moves`, one after the other, made as they are needed.
+pub fn steps(moves: &[Move]) -> impl Iterator<Item = Vec2> + '_ {
+    return moves.iter().flat_map(|x| x.steps());
+}
+
 impl Vec2 {
-    fn plus(&self, other: &Vec2) -> Vec2 {
+    pub fn new(x: i32, y: i32) -> Vec2 {
+        return Vec2 { x, y };
+    }
+
+    pub fn plus(&self, other: &Vec2) -> Vec2 {
         return Vec2 {
             x: self.x + other.x,
             y: self.y + other.y,
         };
     }
 
-    fn minus(&self, other: &Vec2) -> Vec2 {
+    pub fn minus(&self, other: &Vec2) -> Vec2 {
         return Vec2 {
             x: self.x - other.x,
             y: self.y - other.y,
         };
     }
 
-    fn scale(&self, scale: i32) -> Vec2 {
-        return Vec2 {
-            x: self.x * scale,
-            y: self.y * scale,
-        };
-    }
-
     fn direction(&self) -> Vec2 {
         let mut x = 0;
 
@@ -82,151 +101,348 @@ impl Vec2 {
     }
 }
 
-fn parse_steps(str: &str) -> Vec<Vec2> {
-    let split: Vec<&str> = str.split(" ").collect();
-    let direction = split[0];
-    let scale: i32 = split[1].parse().unwrap();
+/// Reads a move like `R 4`, or `UL 2` for a diagonal.
+fn parse_move(str: &str) -> Result<Move, ParseError> {
+    let split: Vec<&str> = str.split_whitespace().collect();
+
+    let direction = match split.first() {
+        Some(direction) => *direction,
+        None => return Err(ParseError::missing_field("direction", str, 1)),
+    };
 
     let step = match direction {
         "U" => UP,
         "R" => RIGHT,
         "D" => DOWN,
         "L" => LEFT,
-        _ => panic!("Skrrrrt"),
+        "UR" => UP.plus(&RIGHT),
+        "UL" => UP.plus(&LEFT),
+        "DR" => DOWN.plus(&RIGHT),
+        "DL" => DOWN.plus(&LEFT),
+        _ => {
+            return Err(ParseError::unexpected_token(
+                direction,
+                column_of(str, direction),
+                "U, R, D, L, UR, UL, DR or DL",
+            ))
+        }
     };
 
-    let mut steps = vec![];
+    let count: u32 = match split.get(1) {
+        Some(count) => parse_number(str, count)?,
+        None => return Err(ParseError::missing_field("step count", str, str.len() + 1)),
+    };
 
-    for _ in 0..scale {
-        steps.push(step.clone())
+    if let Some(token) = split.get(2) {
+        return Err(ParseError::unexpected_token(
+            token,
+            column_of(str, token),
+            "end of line",
+        ));
     }
 
-    return steps;
+    return Ok(Move::new(step, count));
 }
 
-fn next_position(head: &Vec2, tail: &Vec2) -> Vec2 {
-    let difference = head.minus(tail);
+/// Watches a rope, told about every knot that moves.
+pub trait KnotHook {
+    /// Knot number `knot`, 0 being the head, moved from `from` to `to`.
+    fn on_move(&mut self, knot: usize, from: Vec2, to: Vec2);
+}
 
-    if difference.x.abs() >= 2 || difference.y.abs() >= 2 {
-        let step = difference.direction();
+impl<F: FnMut(usize, Vec2, Vec2)> KnotHook for F {
+    fn on_move(&mut self, knot: usize, from: Vec2, to: Vec2) {
+        self(knot, from, to);
+    }
+}
+
+/// Records every cell one knot of a rope visits.
+#[derive(Debug, Clone)]
+pub struct VisitTracker {
+    knot: usize,
+    visited: HashSet<Vec2>,
+}
+
+impl VisitTracker {
+    /// Tracks knot number `knot`, which starts at the origin like every knot of a `Rope`.
+    pub fn new(knot: usize) -> VisitTracker {
+        return VisitTracker {
+            knot,
+            visited: HashSet::from([Vec2::new(0, 0)]),
+        };
+    }
 
-        return tail.plus(&step);
+    pub fn visited(&self) -> &HashSet<Vec2> {
+        return &self.visited;
     }
+}
 
-    return tail.clone();
+impl KnotHook for VisitTracker {
+    fn on_move(&mut self, knot: usize, _from: Vec2, to: Vec2) {
+        if knot == self.knot {
+            self.visited.insert(to);
+        }
+    }
 }
 
-fn part1(filename: &str) -> usize {
-    let steps: Vec<Vec2> = read_lines(filename)
-        .unwrap()
-        .map(|x| x.unwrap())
-        .flat_map(|x| parse_steps(&x[..]))
-        .collect();
+/// A rope of knots that all start at the origin, each one following the one before it as
+/// `rule` says. The knots are kept by index, the head first, and only their current positions
+/// are kept, along with the cells the tail has visited.
+#[derive(Debug, Clone)]
+pub struct Rope<R = Chebyshev> {
+    knots: Vec<Vec2>,
+    tail_visits: VisitTracker,
+    rule: R,
+}
 
-    let mut head_positions = vec![Vec2 { x: 0, y: 0 }];
-    let mut tail_positions = vec![Vec2 { x: 0, y: 0 }];
+impl Rope {
+    /// A rope of `knots` knots that follow the rule of the puzzle, the first one being the
+    /// head. Panics if there are none.
+    pub fn new(knots: usize) -> Rope {
+        return Rope::with_rule(knots, Chebyshev);
+    }
+}
 
-    for head_step in steps {
-        let head_position = head_positions.last().unwrap();
-        let new_head_position = head_position.plus(&head_step);
-        head_positions.push(new_head_position);
+impl<R: FollowRule> Rope<R> {
+    /// A rope of `knots` knots that follow `rule`, the first one being the head. Panics if
+    /// there are none.
+    pub fn with_rule(knots: usize, rule: R) -> Rope<R> {
+        assert!(knots > 0, "a rope needs at least one knot");
 
-        let tail_position = tail_positions.last().unwrap();
+        return Rope {
+            knots: vec![Vec2::new(0, 0); knots],
+            tail_visits: VisitTracker::new(knots - 1),
+            rule,
+        };
+    }
 
-        tail_positions.push(next_position(&new_head_position, tail_position));
+    pub fn knots(&self) -> &[Vec2] {
+        return &self.knots;
     }
 
-    return tail_positions.iter().unique().count();
-}
+    pub fn head(&self) -> Vec2 {
+        return self.knots[0];
+    }
 
-fn part2(filename: &str) -> usize {
-    let steps: Vec<Vec2> = read_lines(filename)
-        .unwrap()
-        .map(|x| x.unwrap())
-        .flat_map(|x| parse_steps(&x[..]))
-        .collect();
+    pub fn tail(&self) -> Vec2 {
+        return self.knots[self.knots.len() - 1];
+    }
 
-    let mut rope: Vec<Vec<Vec2>> = (0..10).map(|i| vec![Vec2 { x: 0, y: 0 }]).collect();
+    /// Every cell the tail has been in, including the origin.
+    pub fn visited(&self) -> &HashSet<Vec2> {
+        return self.tail_visits.visited();
+    }
 
-    for head_step in steps {
-        // Updates the head
-        let head_position = rope[0].last().unwrap();
-        let new_head_position = head_position.plus(&head_step);
-        rope[0].push(new_head_position);
+    /// Moves the head by `step` and lets the other knots follow.
+    pub fn step(&mut self, step: &Vec2) {
+        self.step_with(step, &mut []);
+    }
 
-        for i in 1..rope.len() {
-            let previous_knot_position = rope[i - 1].last().unwrap();
-            let current_knot_position = rope[i].last().unwrap();
+    /// Moves the head by `step` and lets the other knots follow, telling `hooks` about every
+    /// knot that moves, from the head down.
+    pub fn step_with(&mut self, step: &Vec2, hooks: &mut [&mut dyn KnotHook]) {
+        for i in 0..self.knots.len() {
+            let from = self.knots[i];
+            let to = match i {
+                0 => from.plus(step),
+                _ => self.rule.follow(&self.knots[i - 1], &from),
+            };
+
+            // Knots further down only move if this one did
+            if to == from {
+                break;
+            }
 
-            let new_current_knot_position =
-                next_position(previous_knot_position, current_knot_position);
+            self.knots[i] = to;
+            self.tail_visits.on_move(i, from, to);
 
-            //if &new_current_knot_position != current_knot_position {
-            rope[i].push(new_current_knot_position);
-            //}
+            for hook in hooks.iter_mut() {
+                hook.on_move(i, from, to);
+            }
         }
     }
+}
+
+pub fn parse(input: &str) -> Result<Vec<Move>, ParseError> {
+    let mut moves = vec![];
+
+    for (i, line) in Input::from_text(input).lines().enumerate() {
+        moves.push(parse_move(line).map_err(|e| e.at_line(i + 1))?);
+    }
+
+    return Ok(moves);
+}
+
+/// The number of cells the tail of a rope of `knots` knots that follow `rule` visits.
+pub fn tail_visits<R: FollowRule>(moves: &[Move], knots: usize, rule: R) -> usize {
+    let mut rope = Rope::with_rule(knots, rule);
+
+    for step in steps(moves) {
+        rope.step(&step);
+    }
+
+    return rope.visited().len();
+}
 
-    return rope.last().unwrap().iter().unique().count();
+pub fn part1(moves: &[Move]) -> usize {
+    return tail_visits(moves, 2, Chebyshev);
+}
+
+pub fn part2(moves: &[Move]) -> usize {
+    return tail_visits(moves, 10, Chebyshev);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::io_utils::parse_file;
 
     #[test]
     fn parse_right_move_is_correct() {
-        let result = parse_steps("R 4");
+        let result: Vec<Vec2> = parse_move("R 4").unwrap().steps().collect();
 
         assert_eq!(vec![RIGHT, RIGHT, RIGHT, RIGHT], result);
     }
 
     #[test]
     fn parse_left_move_is_correct() {
-        let result = parse_steps("L 5");
+        let result: Vec<Vec2> = parse_move("L 5").unwrap().steps().collect();
 
         assert_eq!(vec![LEFT, LEFT, LEFT, LEFT, LEFT], result);
     }
 
     #[test]
     fn parse_up_move_is_correct() {
-        let result = parse_steps("U 2");
+        let result: Vec<Vec2> = parse_move("U 2").unwrap().steps().collect();
 
         assert_eq!(vec![UP, UP], result);
     }
 
     #[test]
     fn parse_down_move_is_correct() {
-        let result = parse_steps("D 7");
+        let result: Vec<Vec2> = parse_move("D 7").unwrap().steps().collect();
 
         assert_eq!(vec![DOWN, DOWN, DOWN, DOWN, DOWN, DOWN, DOWN], result);
     }
 
     #[test]
-    fn part1_example_is_correct() {
-        let result = part1("./data/day9_example.txt");
+    fn parse_unknown_direction_fails() {
+        let error = parse("R 4\nX 2\n").err().unwrap();
 
-        assert_eq!(13, result);
+        assert_eq!(
+            ParseError::unexpected_token("X", 1, "U, R, D, L, UR, UL, DR or DL").at_line(2),
+            error
+        );
     }
 
     #[test]
-    fn part1_is_correct() {
-        let result = part1("./data/day9.txt");
+    fn parse_bad_count_fails() {
+        assert_eq!(Err(ParseError::bad_number("-3", 3)), parse_move("U -3"));
+    }
 
-        assert_eq!(6314, result);
+    #[test]
+    fn parse_diagonal_move_is_correct() {
+        let moves = parse("UR 2\nDL 1").unwrap();
+
+        assert_eq!(
+            vec![
+                Move::new(Vec2::new(1, -1), 2),
+                Move::new(Vec2::new(-1, 1), 1)
+            ],
+            moves
+        );
     }
 
     #[test]
-    fn part2_example_is_correct() {
-        let result = part2("./data/day9_example.txt");
+    fn parse_missing_count_fails() {
+        let error = parse("U 1\nR 2\nDR").err().unwrap();
 
-        assert_eq!(1, result);
+        assert_eq!(
+            ParseError::missing_field("step count", "DR", 3).at_line(3),
+            error
+        );
+    }
+
+    #[test]
+    fn steps_are_lazy() {
+        let moves = parse("R 1000000000\nU 1000000000").unwrap();
+
+        assert_eq!(2, moves.len());
+        assert_eq!(Some(UP), steps(&moves).nth(1_000_000_000));
     }
 
     #[test]
-    fn part2_is_correct() {
-        let result = part2("./data/day9.txt");
+    fn rope_follows_head() {
+        let mut rope = Rope::new(3);
 
-        assert_eq!(2504, result);
+        for step in [RIGHT, RIGHT, RIGHT, UP, UP] {
+            rope.step(&step);
+        }
+
+        assert_eq!(
+            vec![Vec2::new(3, -2), Vec2::new(3, -1), Vec2::new(2, -1)],
+            rope.knots()
+        );
+        assert_eq!(3, rope.visited().len());
+    }
+
+    #[test]
+    fn hooks_see_every_move() {
+        let mut rope = Rope::new(3);
+        let mut middle = VisitTracker::new(1);
+        let mut moves = vec![];
+
+        for step in [RIGHT, RIGHT, UP, UP] {
+            rope.step_with(
+                &step,
+                &mut [&mut middle, &mut |knot, _, to| moves.push((knot, to))],
+            );
+        }
+
+        assert_eq!(
+            vec![
+                (0, Vec2::new(1, 0)),
+                (0, Vec2::new(2, 0)),
+                (1, Vec2::new(1, 0)),
+                (0, Vec2::new(2, -1)),
+                (0, Vec2::new(2, -2)),
+                (1, Vec2::new(2, -1)),
+                (2, Vec2::new(1, -1)),
+            ],
+            moves
+        );
+        assert_eq!(3, middle.visited().len());
+        assert_eq!(2, rope.visited().len());
+    }
+
+    #[test]
+    fn part2_larger_example_is_correct() {
+        let steps = parse("R 5\nU 8\nL 8\nD 3\nR 17\nD 10\nL 25\nU 20").unwrap();
+
+        assert_eq!(36, part2(&steps));
+    }
+
+    #[test]
+    fn follow_rules_change_the_trail() {
+        let moves = parse_file("./data/day9_example.txt", parse);
+
+        assert_eq!(13, tail_visits(&moves, 2, Slack { max_length: 1 }));
+        assert_eq!(1, tail_visits(&moves, 2, Slack { max_length: 5 }));
+        assert_eq!(13, tail_visits(&moves, 2, Manhattan));
+        assert_eq!(10, tail_visits(&moves, 2, Orthogonal));
+    }
+
+    #[test]
+    fn part1_example_is_correct() {
+        let result = part1(&parse_file("./data/day9_example.txt", parse));
+
+        assert_eq!(13, result);
+    }
+
+    #[test]
+    fn part2_example_is_correct() {
+        let result = part2(&parse_file("./data/day9_example.txt", parse));
+
+        assert_eq!(1, result);
     }
 }
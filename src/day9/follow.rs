@@ -0,0 +1,155 @@
+use super::Vec2;
+
+/// Decides where a knot moves once the knot it follows, its leader, has moved.
+pub trait FollowRule {
+    /// Where `follower` moves to after `leader` moved, possibly where it already is.
+    fn follow(&self, leader: &Vec2, follower: &Vec2) -> Vec2;
+}
+
+impl<R: FollowRule + ?Sized> FollowRule for &R {
+    fn follow(&self, leader: &Vec2, follower: &Vec2) -> Vec2 {
+        return (**self).follow(leader, follower);
+    }
+}
+
+fn chebyshev_distance(a: &Vec2, b: &Vec2) -> i32 {
+    let difference = a.minus(b);
+
+    return difference.x.abs().max(difference.y.abs());
+}
+
+fn manhattan_distance(a: &Vec2, b: &Vec2) -> i32 {
+    let difference = a.minus(b);
+
+    return difference.x.abs() + difference.y.abs();
+}
+
+/// One step along the axis on which `follower` is furthest from `leader`, x on a tie.
+fn orthogonal_step(leader: &Vec2, follower: &Vec2) -> Vec2 {
+    let direction = leader.minus(follower).direction();
+
+    if leader.x.abs_diff(follower.x) >= leader.y.abs_diff(follower.y) {
+        return follower.plus(&Vec2::new(direction.x, 0));
+    }
+
+    return follower.plus(&Vec2::new(0, direction.y));
+}
+
+/// The rule of the puzzle: a knot stays put while it touches its leader, diagonals included,
+/// and otherwise takes one step towards it, diagonally if they are not in the same row or
+/// column.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Chebyshev;
+
+impl FollowRule for Chebyshev {
+    fn follow(&self, leader: &Vec2, follower: &Vec2) -> Vec2 {
+        if chebyshev_distance(leader, follower) >= 2 {
+            return follower.plus(&leader.minus(follower).direction());
+        }
+
+        return *follower;
+    }
+}
+
+/// A knot only touches its leader when it is right next to it in a row or column, and takes
+/// steps along rows and columns, the longest gap first, until it does.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Manhattan;
+
+impl FollowRule for Manhattan {
+    fn follow(&self, leader: &Vec2, follower: &Vec2) -> Vec2 {
+        let mut position = *follower;
+
+        while manhattan_distance(leader, &position) > 1 {
+            position = orthogonal_step(leader, &position);
+        }
+
+        return position;
+    }
+}
+
+/// Like the rule of the puzzle, a knot touches its leader diagonally too, but it can only step
+/// along rows and columns, the longest gap first.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Orthogonal;
+
+impl FollowRule for Orthogonal {
+    fn follow(&self, leader: &Vec2, follower: &Vec2) -> Vec2 {
+        let mut position = *follower;
+
+        while chebyshev_distance(leader, &position) >= 2 {
+            position = orthogonal_step(leader, &position);
+        }
+
+        return position;
+    }
+}
+
+/// An elastic rope: a knot stays put while it is at most `max_length` cells away from its
+/// leader, diagonals counting as one, and is pulled back to that length otherwise. A length
+/// of 1 is the rule of the puzzle.
+#[derive(Debug, Clone, Copy)]
+pub struct Slack {
+    pub max_length: u32,
+}
+
+impl FollowRule for Slack {
+    fn follow(&self, leader: &Vec2, follower: &Vec2) -> Vec2 {
+        let mut position = *follower;
+
+        while chebyshev_distance(leader, &position) as i64 > self.max_length as i64 {
+            position = position.plus(&leader.minus(&position).direction());
+        }
+
+        return position;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGIN: Vec2 = Vec2 { x: 0, y: 0 };
+
+    #[test]
+    fn chebyshev_steps_diagonally() {
+        assert_eq!(ORIGIN, Chebyshev.follow(&Vec2::new(1, 1), &ORIGIN));
+        assert_eq!(Vec2::new(1, 1), Chebyshev.follow(&Vec2::new(2, 1), &ORIGIN));
+    }
+
+    #[test]
+    fn manhattan_steps_orthogonally() {
+        assert_eq!(Vec2::new(1, 0), Manhattan.follow(&Vec2::new(1, 1), &ORIGIN));
+        assert_eq!(Vec2::new(2, 1), Manhattan.follow(&Vec2::new(2, 2), &ORIGIN));
+    }
+
+    #[test]
+    fn orthogonal_allows_diagonal_contact() {
+        assert_eq!(ORIGIN, Orthogonal.follow(&Vec2::new(1, 1), &ORIGIN));
+        assert_eq!(
+            Vec2::new(1, 0),
+            Orthogonal.follow(&Vec2::new(2, 1), &ORIGIN)
+        );
+        assert_eq!(
+            Vec2::new(1, 1),
+            Orthogonal.follow(&Vec2::new(2, 2), &ORIGIN)
+        );
+    }
+
+    #[test]
+    fn slack_pulls_back_to_max_length() {
+        let rule = Slack { max_length: 3 };
+
+        assert_eq!(ORIGIN, rule.follow(&Vec2::new(3, -2), &ORIGIN));
+        assert_eq!(Vec2::new(2, 1), rule.follow(&Vec2::new(5, 1), &ORIGIN));
+    }
+
+    #[test]
+    fn slack_longer_than_any_distance_never_pulls() {
+        let rule = Slack {
+            max_length: u32::MAX,
+        };
+
+        assert_eq!(ORIGIN, rule.follow(&Vec2::new(1, 0), &ORIGIN));
+    }
+}
@@ -0,0 +1,177 @@
+use super::{FollowRule, Rope, Vec2};
+use crate::netpbm::{Image, Rgb};
+use std::collections::HashSet;
+use std::fmt;
+
+/// What a cell of a `Trail` shows, from the most to the least visible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    /// The knot with this number, 0 being the head.
+    Knot(usize),
+    Start,
+    Visited,
+    Empty,
+}
+
+/// A picture of the knots of a rope over the cells one of them has visited, sized to fit all
+/// of them and the start. Drawn like the puzzle does: `H` for the head, `T` for the tail of a
+/// rope of two knots, numbers for the other knots, `s` for the start and `#` for visited cells.
+pub struct Trail<'a> {
+    knots: &'a [Vec2],
+    visited: &'a HashSet<Vec2>,
+    min: Vec2,
+    max: Vec2,
+}
+
+impl<'a> Trail<'a> {
+    pub fn new(knots: &'a [Vec2], visited: &'a HashSet<Vec2>) -> Trail<'a> {
+        let start = Vec2::new(0, 0);
+        let cells = || knots.iter().chain(visited).chain([&start]);
+
+        return Trail {
+            knots,
+            visited,
+            min: Vec2::new(
+                cells().map(|x| x.x).min().unwrap(),
+                cells().map(|x| x.y).min().unwrap(),
+            ),
+            max: Vec2::new(
+                cells().map(|x| x.x).max().unwrap(),
+                cells().map(|x| x.y).max().unwrap(),
+            ),
+        };
+    }
+
+    /// The rope with the cells its tail has visited.
+    pub fn of<R: FollowRule>(rope: &'a Rope<R>) -> Trail<'a> {
+        return Trail::new(rope.knots(), rope.visited());
+    }
+
+    pub fn width(&self) -> u32 {
+        return (self.max.x - self.min.x + 1) as u32;
+    }
+
+    pub fn height(&self) -> u32 {
+        return (self.max.y - self.min.y + 1) as u32;
+    }
+
+    /// The cell at column `x` and row `y` of the picture.
+    fn cell(&self, x: u32, y: u32) -> Cell {
+        let position = Vec2::new(self.min.x + x as i32, self.min.y + y as i32);
+
+        // Knots closer to the head hide the ones behind them
+        if let Some(knot) = self.knots.iter().position(|x| *x == position) {
+            return Cell::Knot(knot);
+        }
+
+        if position == Vec2::new(0, 0) {
+            return Cell::Start;
+        }
+
+        if self.visited.contains(&position) {
+            return Cell::Visited;
+        }
+
+        return Cell::Empty;
+    }
+
+    fn symbol(&self, cell: Cell) -> char {
+        return match cell {
+            Cell::Knot(0) => 'H',
+            Cell::Knot(1) if self.knots.len() == 2 => 'T',
+            Cell::Knot(knot) => char::from_digit(knot as u32, 10).unwrap_or('+'),
+            Cell::Start => 's',
+            Cell::Visited => '#',
+            Cell::Empty => '.',
+        };
+    }
+
+    /// The picture with a pixel per cell: the head red, the other knots orange, the start
+    /// green and visited cells white on black.
+    pub fn to_image(&self) -> Image {
+        let mut image = Image::new(self.width(), self.height(), Rgb::BLACK);
+
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let colour = match self.cell(x, y) {
+                    Cell::Knot(0) => Rgb::new(255, 0, 0),
+                    Cell::Knot(_) => Rgb::new(255, 160, 0),
+                    Cell::Start => Rgb::new(0, 200, 0),
+                    Cell::Visited => Rgb::WHITE,
+                    Cell::Empty => continue,
+                };
+
+                image.set(x, y, colour);
+            }
+        }
+
+        return image;
+    }
+}
+
+impl fmt::Display for Trail<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                write!(f, "{}", self.symbol(self.cell(x, y)))?;
+            }
+
+            writeln!(f)?;
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day9::{parse, steps};
+    use crate::io_utils::Input;
+
+    fn rope(knots: usize, filename: &str) -> Rope {
+        let mut rope = Rope::new(knots);
+
+        let moves = parse(Input::read_file(filename).unwrap().text()).unwrap();
+
+        for step in steps(&moves) {
+            rope.step(&step);
+        }
+
+        return rope;
+    }
+
+    #[test]
+    fn render_example_is_correct() {
+        let rope = rope(2, "./data/day9_example.txt");
+
+        let expected = "\
+..##.
+...##
+.TH##
+....#
+s###.
+";
+
+        assert_eq!(expected, Trail::of(&rope).to_string());
+    }
+
+    #[test]
+    fn render_numbers_knots() {
+        let rope = rope(10, "./data/day9_example.txt");
+
+        // Knots 7 to 9 and the start are hidden under knot 6
+        assert_eq!(".1H3\n.5..\n6...\n", Trail::of(&rope).to_string());
+    }
+
+    #[test]
+    fn to_image_is_sized_to_fit() {
+        let rope = rope(2, "./data/day9_example.txt");
+        let image = Trail::of(&rope).to_image();
+
+        assert_eq!((5, 5), (image.width(), image.height()));
+        assert_eq!(Rgb::new(0, 200, 0), image.get(0, 4));
+        assert_eq!(Rgb::new(255, 0, 0), image.get(2, 2));
+        assert_eq!(Rgb::BLACK, image.get(0, 0));
+    }
+}
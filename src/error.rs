@@ -0,0 +1,155 @@
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// Found something other than what the grammar allows at this point.
+    UnexpectedToken { expected: &'static str },
+    /// The line ended before a required field.
+    MissingField { field: &'static str },
+    /// A field that should be a number could not be read as one.
+    BadNumber,
+}
+
+/// Returned when a puzzle input can not be parsed. Lines and columns are 1-based, and `text` is
+/// the offending part of the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+}
+
+impl ParseError {
+    pub fn unexpected_token(text: &str, column: usize, expected: &'static str) -> ParseError {
+        return ParseError {
+            kind: ParseErrorKind::UnexpectedToken { expected },
+            line: 1,
+            column,
+            text: String::from(text),
+        };
+    }
+
+    pub fn missing_field(field: &'static str, text: &str, column: usize) -> ParseError {
+        return ParseError {
+            kind: ParseErrorKind::MissingField { field },
+            line: 1,
+            column,
+            text: String::from(text),
+        };
+    }
+
+    pub fn bad_number(text: &str, column: usize) -> ParseError {
+        return ParseError {
+            kind: ParseErrorKind::BadNumber,
+            line: 1,
+            column,
+            text: String::from(text),
+        };
+    }
+
+    /// Places an error produced by a single-line parser on `line` of the whole input.
+    pub fn at_line(mut self, line: usize) -> ParseError {
+        self.line = line;
+        return self;
+    }
+
+    /// Moves an error produced while parsing part of a line `offset` columns to the right.
+    pub fn offset_columns(mut self, offset: usize) -> ParseError {
+        self.column += offset;
+        return self;
+    }
+}
+
+/// The 1-based column at which `part` starts. `part` must be a slice of `line`.
+pub fn column_of(line: &str, part: &str) -> usize {
+    return part.as_ptr() as usize - line.as_ptr() as usize + 1;
+}
+
+/// Parses `part`, a slice of `line`, as a number.
+pub fn parse_number<T: FromStr>(line: &str, part: &str) -> Result<T, ParseError> {
+    return part
+        .parse()
+        .map_err(|_| ParseError::bad_number(part, column_of(line, part)));
+}
+
+/// Errors from `FromStr` implementations that can be reported as a `ParseError` once the line
+/// that failed to parse is known.
+pub trait IntoParseError {
+    fn into_parse_error(self, line: &str) -> ParseError;
+}
+
+impl IntoParseError for ParseError {
+    fn into_parse_error(self, _line: &str) -> ParseError {
+        return self;
+    }
+}
+
+impl IntoParseError for ParseIntError {
+    fn into_parse_error(self, line: &str) -> ParseError {
+        return ParseError::bad_number(line, 1);
+    }
+}
+
+impl IntoParseError for Infallible {
+    fn into_parse_error(self, _line: &str) -> ParseError {
+        match self {}
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+
+        return match &self.kind {
+            ParseErrorKind::UnexpectedToken { expected } => {
+                write!(f, "unexpected {:?}, expected {}", self.text, expected)
+            }
+            ParseErrorKind::MissingField { field } => {
+                write!(f, "missing {} in {:?}", field, self.text)
+            }
+            ParseErrorKind::BadNumber => write!(f, "{:?} is not a valid number", self.text),
+        };
+    }
+}
+
+impl Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn column_of_is_correct() {
+        let line = "move 1 from 2 to 3";
+        let split: Vec<&str> = line.split(' ').collect();
+
+        assert_eq!(1, column_of(line, split[0]));
+        assert_eq!(13, column_of(line, split[3]));
+    }
+
+    #[test]
+    fn parse_number_is_correct() {
+        let line = "addx -15";
+
+        assert_eq!(Ok(-15), parse_number::<i32>(line, &line[5..]));
+        assert_eq!(
+            Err(ParseError::bad_number("addx", 1)),
+            parse_number::<i32>(line, &line[..4])
+        );
+    }
+
+    #[test]
+    fn display_is_correct() {
+        let error = ParseError::unexpected_token("Q", 3, "X, Y or Z").at_line(7);
+
+        assert_eq!(
+            "line 7, column 3: unexpected \"Q\", expected X, Y or Z",
+            error.to_string()
+        );
+    }
+}
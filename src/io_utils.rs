@@ -1,11 +1,264 @@
-use std::fs::File;
-use std::io::{self, BufRead};
+use crate::error::{IntoParseError, ParseError};
+use std::fs;
+use std::io::{self, Read};
 use std::path::Path;
+use std::str::{FromStr, Lines};
 
-pub fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
+/// Puzzle input read from a file, stdin or a string. Line endings are normalised to `\n` and
+/// trailing newlines are removed, so days never have to care where the input came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Input {
+    text: String,
+}
+
+/// A group of lines separated from the rest of the input by blank lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Paragraph<'a> {
+    /// The 1-based line number of the first line of the paragraph in the whole input.
+    pub first_line: usize,
+    pub text: &'a str,
+}
+
+impl Input {
+    /// Reads `source`, which is either a path or `-` for stdin.
+    pub fn open(source: &str) -> io::Result<Input> {
+        if source == "-" {
+            return Input::stdin();
+        }
+
+        return Input::read_file(source);
+    }
+
+    pub fn read_file<P>(filename: P) -> io::Result<Input>
+    where
+        P: AsRef<Path>,
+    {
+        return Ok(Input::from_text(&fs::read_to_string(filename)?));
+    }
+
+    pub fn stdin() -> io::Result<Input> {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text)?;
+
+        return Ok(Input::from_text(&text));
+    }
+
+    pub fn from_text(text: &str) -> Input {
+        let text = text.replace("\r\n", "\n");
+
+        return Input {
+            text: String::from(text.trim_end_matches('\n')),
+        };
+    }
+
+    pub fn text(&self) -> &str {
+        return &self.text;
+    }
+
+    pub fn lines(&self) -> Lines<'_> {
+        return self.text.lines();
+    }
+
+    pub fn paragraphs(&self) -> Vec<Paragraph<'_>> {
+        let mut paragraphs = vec![];
+        let mut start: Option<(usize, usize)> = None;
+        let mut offset = 0;
+
+        for (i, line) in self.text.split('\n').enumerate() {
+            let blank = line.trim().is_empty();
+
+            match start {
+                None if !blank => start = Some((i + 1, offset)),
+                Some((first_line, begin)) if blank => {
+                    paragraphs.push(Paragraph {
+                        first_line,
+                        text: &self.text[begin..offset - 1],
+                    });
+                    start = None;
+                }
+                _ => {}
+            }
+
+            offset += line.len() + 1;
+        }
+
+        if let Some((first_line, begin)) = start {
+            paragraphs.push(Paragraph {
+                first_line,
+                text: &self.text[begin..],
+            });
+        }
+
+        return paragraphs;
+    }
+
+    /// Reads a rectangular block of single digits, one row per line.
+    pub fn grid_of_digits(&self) -> Result<Vec<Vec<u32>>, ParseError> {
+        let mut rows: Vec<Vec<u32>> = vec![];
+
+        for (i, line) in self.lines().enumerate() {
+            let mut row = vec![];
+
+            for (column, (offset, x)) in line.char_indices().enumerate() {
+                let token = &line[offset..offset + x.len_utf8()];
+
+                if i > 0 && column >= rows[0].len() {
+                    return Err(
+                        ParseError::unexpected_token(token, column + 1, "end of row")
+                            .at_line(i + 1),
+                    );
+                }
+
+                match x.to_digit(10) {
+                    Some(digit) => row.push(digit),
+                    None => {
+                        return Err(ParseError::unexpected_token(token, column + 1, "a digit")
+                            .at_line(i + 1))
+                    }
+                }
+            }
+
+            if row.is_empty() || (i > 0 && row.len() < rows[0].len()) {
+                return Err(ParseError::missing_field("digit", line, row.len() + 1).at_line(i + 1));
+            }
+
+            rows.push(row);
+        }
+
+        if rows.is_empty() {
+            return Err(ParseError::missing_field("digit", "", 1));
+        }
+
+        return Ok(rows);
+    }
+
+    /// Parses every line as a `T`, reporting errors at their line in the input.
+    pub fn parse_lines<T>(&self) -> Result<Vec<T>, ParseError>
+    where
+        T: FromStr,
+        T::Err: IntoParseError,
+    {
+        return parse_lines(&self.text, 1);
+    }
+}
+
+impl<'a> Paragraph<'a> {
+    pub fn lines(&self) -> Lines<'a> {
+        return self.text.lines();
+    }
+
+    /// Parses every line as a `T`, reporting errors at their line in the whole input.
+    pub fn parse_lines<T>(&self) -> Result<Vec<T>, ParseError>
+    where
+        T: FromStr,
+        T::Err: IntoParseError,
+    {
+        return parse_lines(self.text, self.first_line);
+    }
+}
+
+fn parse_lines<T>(text: &str, first_line: usize) -> Result<Vec<T>, ParseError>
 where
-    P: AsRef<Path>,
+    T: FromStr,
+    T::Err: IntoParseError,
 {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+    return text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            line.parse()
+                .map_err(|e: T::Err| e.into_parse_error(line).at_line(first_line + i))
+        })
+        .collect();
+}
+
+/// Reads `filename` and parses it with `parse`, for tests that need the input of a day.
+#[cfg(test)]
+pub(crate) fn parse_file<T>(filename: &str, parse: fn(&str) -> Result<T, ParseError>) -> T {
+    let input = Input::read_file(filename).unwrap();
+
+    return parse(input.text()).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_text_normalises_line_endings() {
+        let input = Input::from_text("1\r\n2\r\n\r\n3\r\n\n");
+
+        assert_eq!("1\n2\n\n3", input.text());
+    }
+
+    #[test]
+    fn from_text_keeps_leading_whitespace() {
+        let input = Input::from_text("    [D]\n[N] [C]\n");
+
+        assert_eq!("    [D]\n[N] [C]", input.text());
+    }
+
+    #[test]
+    fn paragraphs_are_correct() {
+        let input = Input::from_text("1\n2\n\n3\n\n\n4\n5\n");
+
+        let paragraphs = input.paragraphs();
+
+        assert_eq!(
+            vec![
+                Paragraph {
+                    first_line: 1,
+                    text: "1\n2"
+                },
+                Paragraph {
+                    first_line: 4,
+                    text: "3"
+                },
+                Paragraph {
+                    first_line: 7,
+                    text: "4\n5"
+                },
+            ],
+            paragraphs
+        );
+    }
+
+    #[test]
+    fn paragraph_parse_lines_reports_line_in_input() {
+        let input = Input::from_text("1\n2\n\n3\nx\n");
+
+        let error = input.paragraphs()[1].parse_lines::<u32>().err().unwrap();
+
+        assert_eq!(ParseError::bad_number("x", 1).at_line(5), error);
+    }
+
+    #[test]
+    fn grid_of_digits_is_correct() {
+        let input = Input::from_text("123\n456\n");
+
+        assert_eq!(
+            vec![vec![1, 2, 3], vec![4, 5, 6]],
+            input.grid_of_digits().unwrap()
+        );
+    }
+
+    #[test]
+    fn grid_of_digits_rejects_ragged_rows() {
+        let input = Input::from_text("123\n45\n");
+
+        assert_eq!(
+            Err(ParseError::missing_field("digit", "45", 3).at_line(2)),
+            input.grid_of_digits()
+        );
+    }
+
+    #[test]
+    fn parse_lines_reports_line_number() {
+        let input = Input::from_text("10\n20\n3O\n");
+
+        assert_eq!(
+            Err(ParseError::bad_number("3O", 1).at_line(3)),
+            input.parse_lines::<u32>()
+        );
+    }
 }
@@ -0,0 +1,25 @@
+//! Solutions to Advent of Code 2022. Every day lives in its own module with a `parse` function,
+//! `part1` and `part2`, and the domain types they work on. `registry` lists the days so they can
+//! be run without knowing their input types, which is what the binary and `verify` build on.
+
+#![allow(clippy::needless_return)]
+
+pub mod bench;
+pub mod day1;
+pub mod day10;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod error;
+pub mod io_utils;
+pub mod netpbm;
+pub mod ocr;
+pub mod registry;
+pub mod runner;
+pub mod solution;
+pub mod verify;
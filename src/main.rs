@@ -1,14 +1,783 @@
-mod day1;
-mod day10;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day8;
-mod day9;
-mod io_utils;
-
-fn main() {
-    println!("Hello, world!");
+#![allow(clippy::needless_return)]
+
+use aoc_2022::day10::{
+    self, CRTScreen, Cpu, DebugCommand, Debugger, FrameRecorder, Program, ScreenConfig,
+    SignalSampler, TraceFormat, Tracer,
+};
+use aoc_2022::day9::{self, Chebyshev, FollowRule, Manhattan, Orthogonal, Rope, Slack, Trail};
+use aoc_2022::io_utils::Input;
+use aoc_2022::netpbm::{self, Bitmap, Rgb, TooLargeError};
+use aoc_2022::registry::{self, Day};
+use aoc_2022::{bench, runner, verify};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(about = "Advent of Code 2022 solutions")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// Headers and answers for reading in a terminal
+    Text,
+    /// One JSON object per day and part, with the answer, its type, timings and any error
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum TraceFormatArg {
+    Csv,
+    /// JSON Lines, one object per cycle
+    Jsonl,
+}
+
+/// The largest factor images can be scaled by. Images are also limited to `MAX_PIXELS`.
+const MAX_SCALE: i64 = 64;
+
+/// How to encode the images of the day 10 screen.
+#[derive(Args)]
+struct ImageArgs {
+    #[arg(short, long, value_enum, default_value_t = ImageFormat::Ppm)]
+    format: ImageFormat,
+
+    /// The width and height in image pixels of a screen pixel, at most 64
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..=MAX_SCALE))]
+    scale: u32,
+
+    /// The colour of lit pixels in PPM images
+    #[arg(long, default_value = "#ffffff")]
+    on: Rgb,
+
+    /// The colour of dark pixels in PPM images
+    #[arg(long, default_value = "#000000")]
+    off: Rgb,
+}
+
+impl ImageArgs {
+    fn extension(&self) -> &'static str {
+        return match self.format {
+            ImageFormat::Pbm => "pbm",
+            ImageFormat::Ppm => "ppm",
+        };
+    }
+
+    fn encode(&self, bitmap: &Bitmap) -> Result<Vec<u8>, TooLargeError> {
+        return match self.format {
+            ImageFormat::Pbm => Ok(bitmap.scale(self.scale)?.to_pbm()),
+            ImageFormat::Ppm => Ok(bitmap
+                .to_image(self.on, self.off)
+                .scale(self.scale)?
+                .to_ppm()),
+        };
+    }
+}
+
+/// The largest width and height of the day 10 screen, so that its images fit in memory.
+const MAX_SCREEN_SIZE: i64 = 4096;
+
+/// The geometry of the day 10 screen, defaulting to the one of the puzzle.
+#[derive(Args)]
+struct ScreenArgs {
+    /// The number of pixels in a row, at most 4096
+    #[arg(long, default_value_t = 40, value_parser = clap::value_parser!(u32).range(1..=MAX_SCREEN_SIZE))]
+    width: u32,
+
+    /// The number of rows, at most 4096
+    #[arg(long, default_value_t = 6, value_parser = clap::value_parser!(u32).range(1..=MAX_SCREEN_SIZE))]
+    height: u32,
+
+    /// The width of the sprite in pixels, centred on X, at most 4096
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..=MAX_SCREEN_SIZE))]
+    sprite_width: u32,
+
+    /// Stops drawing once the frame is full instead of starting over at the top left
+    #[arg(long)]
+    no_wrap: bool,
+
+    /// The value of X when the program starts
+    #[arg(long, default_value_t = 1, allow_negative_numbers = true)]
+    start_x: i32,
+}
+
+impl ScreenArgs {
+    fn config(&self) -> ScreenConfig {
+        return ScreenConfig {
+            width: self.width,
+            height: self.height,
+            sprite_width: self.sprite_width,
+            wrap: !self.no_wrap,
+            start_x: self.start_x,
+        };
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ImageFormat {
+    /// Black and white PBM, lit pixels are black
+    Pbm,
+    /// Colour PPM
+    Ppm,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Runs one or more days and prints the answers
+    Run {
+        /// The day to run
+        #[arg(short, long, required_unless_present = "all")]
+        day: Option<u8>,
+
+        /// The part to run, both parts are run if omitted
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: Option<u8>,
+
+        /// The puzzle input, defaults to data/day<N>.txt. Use - to read from stdin
+        #[arg(short, long, conflicts_with = "all")]
+        input: Option<String>,
+
+        /// Runs every registered day
+        #[arg(short, long, conflicts_with = "day")]
+        all: bool,
+
+        /// How to print the answers
+        #[arg(short, long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+    /// Checks every solution against a file of known answers
+    Verify {
+        /// The answers file, input files are looked up next to it
+        #[arg(long, default_value = "data/answers.toml")]
+        answers: PathBuf,
+    },
+    /// Times parsing and solving of every registered day
+    Bench {
+        /// Only benchmarks this day
+        #[arg(short, long)]
+        day: Option<u8>,
+
+        /// The number of timed runs of every step
+        #[arg(short = 'n', long, default_value_t = 10)]
+        iterations: usize,
+
+        /// The number of untimed runs of every step before timing starts
+        #[arg(short, long, default_value_t = 2)]
+        warmup: usize,
+
+        /// Prints the report as JSON, for comparing runs between commits
+        #[arg(long)]
+        json: bool,
+    },
+    /// Saves what a day 10 program draws on the screen as Netpbm images
+    Export {
+        /// The program to draw
+        #[arg(short, long, default_value = "data/day10.txt")]
+        input: String,
+
+        /// Where to save the final image
+        #[arg(short, long, required_unless_present = "frames")]
+        output: Option<PathBuf>,
+
+        /// A directory to save the screen after every cycle to, as numbered images
+        #[arg(long)]
+        frames: Option<PathBuf>,
+
+        #[command(flatten)]
+        image: ImageArgs,
+
+        #[command(flatten)]
+        screen: ScreenArgs,
+    },
+    /// Steps through a day 10 program, reading commands from stdin
+    Debug {
+        /// The program to debug
+        #[arg(short, long, default_value = "data/day10.txt")]
+        input: String,
+
+        #[command(flatten)]
+        screen: ScreenArgs,
+    },
+    /// Writes what happens during every cycle of a day 10 program
+    Trace {
+        /// The program to trace
+        #[arg(short, long, default_value = "data/day10.txt")]
+        input: String,
+
+        /// Where to save the trace instead of printing it
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        #[arg(short, long, value_enum, default_value_t = TraceFormatArg::Csv)]
+        format: TraceFormatArg,
+
+        #[command(flatten)]
+        screen: ScreenArgs,
+    },
+    /// Runs a day 10 program while it is being read, without keeping it, and prints the sum of
+    /// the signal strengths of part 1. Labels and jumps are not supported
+    Stream {
+        /// The program to run. Use - to read from stdin
+        #[arg(short, long, default_value = "data/day10.txt")]
+        input: String,
+    },
+    /// Writes a day 10 program that draws the given text or picture
+    Generate {
+        /// The text to draw, in capital letters
+        #[arg(
+            short,
+            long,
+            required_unless_present = "bitmap",
+            conflicts_with = "bitmap"
+        )]
+        text: Option<String>,
+
+        /// A file with the picture to draw, a row of `#` and `.` per line of the screen
+        #[arg(short, long)]
+        bitmap: Option<PathBuf>,
+
+        /// Where to save the program instead of printing it
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        #[command(flatten)]
+        screen: ScreenArgs,
+    },
+    /// Draws where the rope of day 9 ends up and the cells its tail visited
+    Rope {
+        /// The moves of the head
+        #[arg(short, long, default_value = "data/day9.txt")]
+        input: String,
+
+        /// The number of knots, including the head and the tail
+        #[arg(short, long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+        knots: u32,
+
+        /// Saves the picture as a PPM image instead of printing it
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// The width and height in image pixels of a cell, at most 64
+        #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..=MAX_SCALE))]
+        scale: u32,
+
+        /// How knots follow the one before them
+        #[arg(short, long, value_enum, default_value_t = RuleArg::Chebyshev)]
+        rule: RuleArg,
+
+        /// How far a knot can get from the one before it with the slack rule
+        #[arg(long, default_value_t = 2)]
+        max_length: u32,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum RuleArg {
+    /// The rule of the puzzle, touching diagonally counts
+    Chebyshev,
+    /// Knots only touch in rows and columns and step along them
+    Manhattan,
+    /// Touching diagonally counts, but knots only step along rows and columns
+    Orthogonal,
+    /// Knots are pulled back once further than --max-length away
+    Slack,
+}
+
+fn default_input(day: u8) -> String {
+    return format!("data/day{}.txt", day);
+}
+
+fn run_bench(number: Option<u8>, iterations: usize, warmup: usize, json: bool) -> bool {
+    let days: Vec<Day> = match number {
+        Some(number) => match registry::find(number) {
+            Some(day) => vec![day],
+            None => {
+                eprintln!("Day {} is not implemented", number);
+                return false;
+            }
+        },
+        None => registry::days(),
+    };
+
+    // A day that cannot be benchmarked fails the run, but the others are still reported
+    let mut success = true;
+    let mut reports = vec![];
+    for day in days {
+        let filename = default_input(day.number);
+
+        let input = match Input::open(&filename) {
+            Ok(input) => input,
+            Err(error) => {
+                eprintln!("Day {}: could not read {}: {}", day.number, filename, error);
+                success = false;
+                continue;
+            }
+        };
+
+        match bench::bench(&day, input.text(), warmup, iterations) {
+            Ok(report) => reports.push(report),
+            Err(error) => {
+                eprintln!(
+                    "Day {}: could not parse {}: {}",
+                    day.number, filename, error
+                );
+                success = false;
+            }
+        }
+    }
+
+    if json {
+        println!("{}", bench::to_json(&reports, warmup, iterations));
+    } else {
+        bench::print_report(&reports);
+    }
+
+    return success;
+}
+
+/// Reads and assembles a day 10 program, warning when it does not fill the screen exactly or
+/// does not end.
+fn load_program(filename: &str, config: &ScreenConfig) -> Option<Program> {
+    return match Input::read_file(filename).map(|x| day10::parse(x.text())) {
+        Ok(Ok(program)) => {
+            match day10::cycle_count(&program, *config) {
+                Ok(cycles) => {
+                    if let Some(warning) = config.check(cycles) {
+                        eprintln!("Warning: {}", warning);
+                    }
+                }
+                Err(error) => eprintln!("Warning: {}", error),
+            }
+
+            Some(program)
+        }
+        Ok(Err(error)) => {
+            eprintln!("Could not parse {}: {}", filename, error);
+            None
+        }
+        Err(error) => {
+            eprintln!("Could not read {}: {}", filename, error);
+            None
+        }
+    };
+}
+
+fn run_export(
+    filename: &str,
+    output: Option<PathBuf>,
+    frames: Option<PathBuf>,
+    image: &ImageArgs,
+    config: ScreenConfig,
+) -> bool {
+    let program = match load_program(filename, &config) {
+        Some(program) => program,
+        None => return false,
+    };
+
+    if let Err(error) = netpbm::scaled_size(config.width, config.height, image.scale) {
+        eprintln!("Could not export {}: {}", filename, error);
+        return false;
+    }
+
+    if let Some(directory) = &frames {
+        if let Err(error) = fs::create_dir_all(directory) {
+            eprintln!("Could not create {}: {}", directory.display(), error);
+            return false;
+        }
+    }
+
+    // Pad the numbers so the frames sort in order
+    let digits = day10::cycle_count(&program, config)
+        .unwrap_or(0)
+        .to_string()
+        .len()
+        .max(4);
+    let save = |i: usize, frame: &Bitmap| -> Result<(), String> {
+        let directory = match &frames {
+            Some(directory) => directory,
+            None => return Ok(()),
+        };
+        let path = directory.join(format!("{:0digits$}.{}", i, image.extension()));
+        let bytes = image.encode(frame).map_err(|e| e.to_string())?;
+
+        return fs::write(&path, bytes)
+            .map_err(|e| format!("could not write {}: {}", path.display(), e));
+    };
+
+    let mut recorder = FrameRecorder::new(CRTScreen::with_config(config), save);
+    let mut cpu = config.cpu();
+    let result = cpu.run(&program.ops, &mut [&mut recorder]);
+    let saved = recorder.frames();
+
+    let screen = match recorder.finish() {
+        Ok(screen) => screen,
+        Err(error) => {
+            eprintln!("Could not save the frames: {}", error);
+            return false;
+        }
+    };
+
+    if let Err(error) = result {
+        eprintln!("Could not draw {}: {}", filename, error);
+        return false;
+    }
+
+    if let Some(output) = output {
+        let bytes = match image.encode(&screen.bitmap()) {
+            Ok(bytes) => bytes,
+            Err(error) => {
+                eprintln!("Could not export {}: {}", filename, error);
+                return false;
+            }
+        };
+
+        if let Err(error) = fs::write(&output, bytes) {
+            eprintln!("Could not write {}: {}", output.display(), error);
+            return false;
+        }
+    }
+
+    if let Some(directory) = frames {
+        println!("Saved {} frames to {}", saved, directory.display());
+    }
+
+    return true;
+}
+
+fn run_rope(
+    filename: &str,
+    knots: u32,
+    output: Option<PathBuf>,
+    scale: u32,
+    rule: &dyn FollowRule,
+) -> bool {
+    let moves = match Input::read_file(filename).map(|x| day9::parse(x.text())) {
+        Ok(Ok(moves)) => moves,
+        Ok(Err(error)) => {
+            eprintln!("Could not parse {}: {}", filename, error);
+            return false;
+        }
+        Err(error) => {
+            eprintln!("Could not read {}: {}", filename, error);
+            return false;
+        }
+    };
+
+    let mut rope = Rope::with_rule(knots as usize, rule);
+
+    for step in day9::steps(&moves) {
+        rope.step(&step);
+    }
+
+    let trail = Trail::of(&rope);
+
+    match output {
+        Some(output) => {
+            let image = match trail.to_image().scale(scale) {
+                Ok(image) => image,
+                Err(error) => {
+                    eprintln!("Could not draw {}: {}", filename, error);
+                    return false;
+                }
+            };
+
+            if let Err(error) = fs::write(&output, image.to_ppm()) {
+                eprintln!("Could not write {}: {}", output.display(), error);
+                return false;
+            }
+        }
+        None => print!("{}", trail),
+    }
+
+    return true;
+}
+
+fn run_generate(
+    text: Option<String>,
+    bitmap: Option<PathBuf>,
+    output: Option<PathBuf>,
+    config: ScreenConfig,
+) -> bool {
+    let program = match (text, bitmap) {
+        (Some(text), _) => day10::generate_text(&text, config),
+        (None, Some(filename)) => match fs::read_to_string(&filename) {
+            Ok(text) => day10::generate(&Bitmap::from_text(&text), config),
+            Err(error) => {
+                eprintln!("Could not read {}: {}", filename.display(), error);
+                return false;
+            }
+        },
+        (None, None) => unreachable!("clap requires --text or --bitmap"),
+    };
+
+    let program = match program {
+        Ok(program) => program,
+        Err(error) => {
+            eprintln!("Could not generate a program: {}", error);
+            return false;
+        }
+    };
+
+    match output {
+        Some(output) => {
+            if let Err(error) = fs::write(&output, program.to_string()) {
+                eprintln!("Could not write {}: {}", output.display(), error);
+                return false;
+            }
+        }
+        None => print!("{}", program),
+    }
+
+    return true;
+}
+
+fn run_trace(
+    filename: &str,
+    output: Option<PathBuf>,
+    format: TraceFormatArg,
+    config: ScreenConfig,
+) -> bool {
+    let program = match load_program(filename, &config) {
+        Some(program) => program,
+        None => return false,
+    };
+
+    let writer: Box<dyn Write> = match &output {
+        Some(output) => match fs::File::create(output) {
+            Ok(file) => Box::new(io::BufWriter::new(file)),
+            Err(error) => {
+                eprintln!("Could not create {}: {}", output.display(), error);
+                return false;
+            }
+        },
+        None => Box::new(io::stdout().lock()),
+    };
+
+    let format = match format {
+        TraceFormatArg::Csv => TraceFormat::Csv,
+        TraceFormatArg::Jsonl => TraceFormat::JsonLines,
+    };
+
+    let mut tracer = Tracer::new(&program, config, format, writer);
+    let mut cpu = config.cpu();
+    let result = cpu.run(&program.ops, &mut [&mut tracer]);
+
+    if let Err(error) = tracer.finish() {
+        eprintln!("Could not write the trace: {}", error);
+        return false;
+    }
+
+    if let Err(error) = result {
+        eprintln!("Stopped tracing {}: {}", filename, error);
+        return false;
+    }
+
+    return true;
+}
+
+fn run_stream(filename: &str) -> bool {
+    let reader: Box<dyn BufRead> = if filename == "-" {
+        Box::new(io::stdin().lock())
+    } else {
+        match fs::File::open(filename) {
+            Ok(file) => Box::new(io::BufReader::new(file)),
+            Err(error) => {
+                eprintln!("Could not read {}: {}", filename, error);
+                return false;
+            }
+        }
+    };
+
+    let mut sampler = SignalSampler::every(20, 40).until(220);
+    let mut cpu = Cpu::new();
+
+    let result = itertools::process_results(reader.lines(), |lines| {
+        itertools::process_results(day10::stream_ops(lines), |ops| {
+            cpu.run_stream(ops, &mut [&mut sampler])
+        })
+    });
+
+    match result {
+        Ok(Ok(Ok(()))) => {}
+        Ok(Ok(Err(error))) => {
+            eprintln!("Could not run {}: {}", filename, error);
+            return false;
+        }
+        Ok(Err(error)) => {
+            eprintln!("Could not parse {}: {}", filename, error);
+            return false;
+        }
+        Err(error) => {
+            eprintln!("Could not read {}: {}", filename, error);
+            return false;
+        }
+    }
+
+    println!("{} ({} cycles)", sampler.total(), cpu.cycle);
+
+    return true;
+}
+
+fn run_debugger(filename: &str, config: ScreenConfig) -> bool {
+    let program = match load_program(filename, &config) {
+        Some(program) => program,
+        None => return false,
+    };
+
+    let mut debugger = Debugger::with_config(&program, config);
+    let mut last: Option<DebugCommand> = None;
+
+    println!("{}", debugger.status());
+    println!("Type help for a list of commands");
+
+    let stdin = io::stdin();
+
+    loop {
+        print!("(debug) ");
+        io::stdout().flush().ok();
+
+        let mut line = String::new();
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(error) => {
+                eprintln!("Could not read command: {}", error);
+                return false;
+            }
+        }
+
+        let command = match (line.trim().is_empty(), last) {
+            (true, Some(command)) => command,
+            _ => match line.parse() {
+                Ok(command) => command,
+                Err(error) => {
+                    eprintln!("{}", error);
+                    continue;
+                }
+            },
+        };
+
+        if command == DebugCommand::Quit {
+            break;
+        }
+
+        println!("{}", debugger.run_command(&command).trim_end_matches('\n'));
+        last = Some(command);
+    }
+
+    return true;
+}
+
+fn main() -> ExitCode {
+    // Debug output of the solutions is off unless asked for with RUST_LOG
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("off")).init();
+
+    let cli = Cli::parse();
+
+    let success = match cli.command {
+        Command::Run {
+            day,
+            part,
+            input,
+            all,
+            format,
+        } => {
+            let parts: Vec<u8> = match part {
+                Some(part) => vec![part],
+                None => vec![1, 2],
+            };
+
+            let mut reports = vec![];
+            if all {
+                for day in registry::days() {
+                    reports.extend(runner::run(&day, &parts, &default_input(day.number)));
+                }
+            } else {
+                let number = day.unwrap();
+                match registry::find(number) {
+                    Some(day) => {
+                        let input = input.unwrap_or_else(|| default_input(number));
+                        reports.extend(runner::run(&day, &parts, &input));
+                    }
+                    None => {
+                        eprintln!("Day {} is not implemented", number);
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+
+            match format {
+                Format::Text => runner::print_report(&reports),
+                Format::Json => println!("{}", runner::to_json(&reports)),
+            }
+
+            reports.iter().all(|x| x.passed())
+        }
+        Command::Verify { answers } => match verify::load_answers(&answers) {
+            Ok(expectations) => {
+                let data_dir = answers.parent().unwrap_or(Path::new("."));
+                let outcomes = verify::verify(&expectations, data_dir);
+                verify::print_report(&outcomes);
+                outcomes.iter().all(|x| x.passed())
+            }
+            Err(error) => {
+                eprintln!("Could not read {}: {}", answers.display(), error);
+                false
+            }
+        },
+        Command::Bench {
+            day,
+            iterations,
+            warmup,
+            json,
+        } => run_bench(day, iterations, warmup, json),
+        Command::Export {
+            input,
+            output,
+            frames,
+            image,
+            screen,
+        } => run_export(&input, output, frames, &image, screen.config()),
+        Command::Debug { input, screen } => run_debugger(&input, screen.config()),
+        Command::Trace {
+            input,
+            output,
+            format,
+            screen,
+        } => run_trace(&input, output, format, screen.config()),
+        Command::Stream { input } => run_stream(&input),
+        Command::Rope {
+            input,
+            knots,
+            output,
+            scale,
+            rule,
+            max_length,
+        } => {
+            let rule: &dyn FollowRule = match rule {
+                RuleArg::Chebyshev => &Chebyshev,
+                RuleArg::Manhattan => &Manhattan,
+                RuleArg::Orthogonal => &Orthogonal,
+                RuleArg::Slack => &Slack { max_length },
+            };
+
+            run_rope(&input, knots, output, scale, rule)
+        }
+        Command::Generate {
+            text,
+            bitmap,
+            output,
+            screen,
+        } => run_generate(text, bitmap, output, screen.config()),
+    };
+
+    if success {
+        return ExitCode::SUCCESS;
+    }
+
+    return ExitCode::FAILURE;
 }
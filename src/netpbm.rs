@@ -0,0 +1,317 @@
+use crate::error::ParseError;
+use std::fmt;
+use std::str::FromStr;
+
+/// The most pixels an image may have when scaled, so that it fits in memory.
+pub const MAX_PIXELS: u64 = 1 << 26;
+
+/// A scaled image would have more than `MAX_PIXELS` pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TooLargeError {
+    pub width: u64,
+    pub height: u64,
+}
+
+impl fmt::Display for TooLargeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(
+            f,
+            "a {}x{} image is too large, the limit is {} pixels",
+            self.width, self.height, MAX_PIXELS
+        );
+    }
+}
+
+/// The size of a `width` by `height` image scaled by `factor`.
+pub fn scaled_size(width: u32, height: u32, factor: u32) -> Result<(u32, u32), TooLargeError> {
+    let (scaled_width, scaled_height) =
+        (width as u64 * factor as u64, height as u64 * factor as u64);
+    let error = TooLargeError {
+        width: scaled_width,
+        height: scaled_height,
+    };
+
+    if scaled_width
+        .checked_mul(scaled_height)
+        .is_none_or(|x| x > MAX_PIXELS)
+    {
+        return Err(error);
+    }
+
+    return Ok((scaled_width as u32, scaled_height as u32));
+}
+
+/// The index of pixel `x`, `y` in the rows of an image `width` pixels wide.
+fn index(width: u32, x: u32, y: u32) -> usize {
+    return y as usize * width as usize + x as usize;
+}
+
+/// A colour with 8 bits per channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb { r: 0, g: 0, b: 0 };
+    pub const WHITE: Rgb = Rgb {
+        r: 255,
+        g: 255,
+        b: 255,
+    };
+
+    pub fn new(r: u8, g: u8, b: u8) -> Rgb {
+        return Rgb { r, g, b };
+    }
+}
+
+impl FromStr for Rgb {
+    type Err = ParseError;
+
+    /// Reads a colour written like `#ff8800`.
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let error = || ParseError::unexpected_token(str, 1, "a colour like #ff8800");
+
+        let hex = match str.strip_prefix('#') {
+            Some(hex) if hex.len() == 6 && hex.is_ascii() => hex,
+            _ => return Err(error()),
+        };
+
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| error());
+
+        return Ok(Rgb::new(channel(0)?, channel(2)?, channel(4)?));
+    }
+}
+
+/// An image of pixels that are either on or off, saved as PBM.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bitmap {
+    width: u32,
+    height: u32,
+    bits: Vec<bool>,
+}
+
+impl Bitmap {
+    pub fn new(width: u32, height: u32) -> Bitmap {
+        return Bitmap {
+            width,
+            height,
+            bits: vec![false; width as usize * height as usize],
+        };
+    }
+
+    /// A bitmap in which `on(x, y)` tells whether a pixel is on.
+    pub fn from_fn<F: Fn(u32, u32) -> bool>(width: u32, height: u32, on: F) -> Bitmap {
+        let mut bitmap = Bitmap::new(width, height);
+
+        for y in 0..height {
+            for x in 0..width {
+                bitmap.set(x, y, on(x, y));
+            }
+        }
+
+        return bitmap;
+    }
+
+    /// Reads a picture drawn with `#` for lit and `.` for dark pixels, a row per line, the way
+    /// the day 10 screen prints. Short rows are padded with dark pixels.
+    pub fn from_text(text: &str) -> Bitmap {
+        let rows: Vec<&[u8]> = text.lines().map(|x| x.as_bytes()).collect();
+        let width = rows.iter().map(|x| x.len()).max().unwrap_or(0);
+
+        return Bitmap::from_fn(width as u32, rows.len() as u32, |x, y| {
+            rows[y as usize].get(x as usize) == Some(&b'#')
+        });
+    }
+
+    pub fn width(&self) -> u32 {
+        return self.width;
+    }
+
+    pub fn height(&self) -> u32 {
+        return self.height;
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> bool {
+        return self.bits[index(self.width, x, y)];
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, on: bool) {
+        self.bits[index(self.width, x, y)] = on;
+    }
+
+    /// Makes every pixel a `factor` by `factor` square, unless that makes it too large.
+    pub fn scale(&self, factor: u32) -> Result<Bitmap, TooLargeError> {
+        let (width, height) = scaled_size(self.width, self.height, factor)?;
+
+        return Ok(Bitmap::from_fn(width, height, |x, y| {
+            self.get(x / factor, y / factor)
+        }));
+    }
+
+    /// Paints the pixels that are on in `on` and the others in `off`.
+    pub fn to_image(&self, on: Rgb, off: Rgb) -> Image {
+        let mut image = Image::new(self.width, self.height, off);
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.get(x, y) {
+                    image.set(x, y, on);
+                }
+            }
+        }
+
+        return image;
+    }
+
+    /// Encodes the bitmap as a binary PBM (P4), in which 1 is black.
+    pub fn to_pbm(&self) -> Vec<u8> {
+        let mut bytes = format!("P4\n{} {}\n", self.width, self.height).into_bytes();
+
+        for y in 0..self.height {
+            // Every row starts on a new byte
+            for start in (0..self.width).step_by(8) {
+                let mut byte = 0;
+
+                for x in start..(start + 8).min(self.width) {
+                    if self.get(x, y) {
+                        byte |= 0x80 >> (x - start);
+                    }
+                }
+
+                bytes.push(byte);
+            }
+        }
+
+        return bytes;
+    }
+}
+
+/// A colour image, saved as PPM.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32, background: Rgb) -> Image {
+        return Image {
+            width,
+            height,
+            pixels: vec![background; width as usize * height as usize],
+        };
+    }
+
+    pub fn width(&self) -> u32 {
+        return self.width;
+    }
+
+    pub fn height(&self) -> u32 {
+        return self.height;
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Rgb {
+        return self.pixels[index(self.width, x, y)];
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, colour: Rgb) {
+        self.pixels[index(self.width, x, y)] = colour;
+    }
+
+    /// Makes every pixel a `factor` by `factor` square, unless that makes it too large.
+    pub fn scale(&self, factor: u32) -> Result<Image, TooLargeError> {
+        let (width, height) = scaled_size(self.width, self.height, factor)?;
+        let mut image = Image::new(width, height, Rgb::BLACK);
+
+        for y in 0..image.height {
+            for x in 0..image.width {
+                image.set(x, y, self.get(x / factor, y / factor));
+            }
+        }
+
+        return Ok(image);
+    }
+
+    /// Encodes the image as a binary PPM (P6).
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut bytes = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+
+        for pixel in &self.pixels {
+            bytes.extend([pixel.r, pixel.g, pixel.b]);
+        }
+
+        return bytes;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rgb_is_correct() {
+        assert_eq!(Ok(Rgb::new(255, 136, 0)), "#ff8800".parse());
+        assert_eq!(
+            Err(ParseError::unexpected_token(
+                "red",
+                1,
+                "a colour like #ff8800"
+            )),
+            "red".parse::<Rgb>()
+        );
+    }
+
+    #[test]
+    fn from_text_is_correct() {
+        let bitmap = Bitmap::from_text("#.#\n.#\n");
+
+        assert_eq!((3, 2), (bitmap.width(), bitmap.height()));
+        assert_eq!(Bitmap::from_fn(3, 2, |x, y| (x + y) % 2 == 0), bitmap);
+    }
+
+    #[test]
+    fn to_pbm_pads_rows() {
+        let bitmap = Bitmap::from_fn(9, 2, |x, y| x == y || x == 8);
+
+        assert_eq!(b"P4\n9 2\n\x80\x80\x40\x80".to_vec(), bitmap.to_pbm());
+    }
+
+    #[test]
+    fn to_ppm_is_correct() {
+        let bitmap = Bitmap::from_fn(2, 1, |x, _| x == 0);
+
+        let image = bitmap
+            .to_image(Rgb::new(1, 2, 3), Rgb::BLACK)
+            .scale(2)
+            .unwrap();
+
+        assert_eq!((4, 2), (image.width(), image.height()));
+        assert_eq!(
+            b"P6\n4 2\n255\n\x01\x02\x03\x01\x02\x03\0\0\0\0\0\0\x01\x02\x03\x01\x02\x03\0\0\0\0\0\0"
+                .to_vec(),
+            image.to_ppm()
+        );
+    }
+
+    #[test]
+    fn scale_rejects_images_that_are_too_large() {
+        let bitmap = Bitmap::new(40, 6);
+
+        assert_eq!(
+            Err(TooLargeError {
+                width: 4_000_000,
+                height: 600_000
+            }),
+            bitmap.scale(100_000)
+        );
+        assert!(bitmap
+            .to_image(Rgb::WHITE, Rgb::BLACK)
+            .scale(u32::MAX)
+            .is_err());
+        assert_eq!(Ok((80, 12)), scaled_size(40, 6, 2));
+    }
+}
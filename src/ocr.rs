@@ -0,0 +1,202 @@
+use crate::netpbm::Bitmap;
+use std::error::Error;
+use std::fmt;
+
+pub const GLYPH_WIDTH: u32 = 4;
+pub const GLYPH_HEIGHT: u32 = 6;
+
+/// Glyphs are drawn this many columns apart, leaving one blank column between letters.
+const GLYPH_SPACING: u32 = GLYPH_WIDTH + 1;
+
+/// The block letters of the puzzles that draw text, row by row.
+const FONT: [(char, [&str; 6]); 17] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OcrError {
+    /// The image is not exactly one glyph high.
+    BadHeight { height: u32 },
+    /// The glyph starting at `column` (0-based, like screen coordinates) is not a known letter.
+    UnknownGlyph { column: u32, glyph: String },
+    /// The letter is not in the font, so it cannot be rendered.
+    UnknownLetter { letter: char },
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            OcrError::BadHeight { height } => write!(
+                f,
+                "text is {} pixels high, expected {}",
+                height, GLYPH_HEIGHT
+            ),
+            OcrError::UnknownGlyph { column, glyph } => {
+                write!(f, "unknown glyph at column {}:\n{}", column, glyph)
+            }
+            OcrError::UnknownLetter { letter } => {
+                write!(f, "there is no glyph for {:?}", letter)
+            }
+        };
+    }
+}
+
+impl Error for OcrError {}
+
+/// Reads the letters of a `width` by `height` image in which `lit(x, y)` tells whether a pixel
+/// is on. Blank glyphs read as spaces, and blanks at the end are dropped.
+pub fn read<F>(width: u32, height: u32, lit: F) -> Result<String, OcrError>
+where
+    F: Fn(u32, u32) -> bool,
+{
+    if height != GLYPH_HEIGHT {
+        return Err(OcrError::BadHeight { height });
+    }
+
+    let mut text = String::new();
+
+    for column in (0..width).step_by(GLYPH_SPACING as usize) {
+        if column + GLYPH_WIDTH > width {
+            break;
+        }
+
+        let rows: Vec<String> = (0..GLYPH_HEIGHT)
+            .map(|y| {
+                (column..column + GLYPH_WIDTH)
+                    .map(|x| if lit(x, y) { '#' } else { '.' })
+                    .collect()
+            })
+            .collect();
+
+        if rows.iter().all(|row| !row.contains('#')) {
+            text.push(' ');
+            continue;
+        }
+
+        let letter = FONT
+            .iter()
+            .find(|(_, glyph)| glyph.iter().zip(&rows).all(|(a, b)| a == b))
+            .map(|(letter, _)| *letter);
+
+        match letter {
+            Some(letter) => text.push(letter),
+            None => {
+                return Err(OcrError::UnknownGlyph {
+                    column,
+                    glyph: rows.join("\n"),
+                })
+            }
+        }
+    }
+
+    return Ok(String::from(text.trim_end()));
+}
+
+/// Reads the letters of an image drawn with `#` for lit and `.` for dark pixels.
+pub fn read_bitmap(bitmap: &str) -> Result<String, OcrError> {
+    let bitmap = Bitmap::from_text(bitmap);
+
+    return read(bitmap.width(), bitmap.height(), |x, y| bitmap.get(x, y));
+}
+
+/// Draws `text` the way the puzzles do, one glyph every 5 columns, so that `read` gives it back.
+/// Spaces leave a blank glyph.
+pub fn render(text: &str) -> Result<Bitmap, OcrError> {
+    let mut glyphs = vec![];
+
+    for letter in text.chars() {
+        let glyph = match FONT.iter().find(|(x, _)| *x == letter) {
+            Some((_, glyph)) => Some(glyph),
+            None if letter == ' ' => None,
+            None => return Err(OcrError::UnknownLetter { letter }),
+        };
+
+        glyphs.push(glyph);
+    }
+
+    let width = glyphs.len() as u32 * GLYPH_SPACING;
+
+    return Ok(Bitmap::from_fn(width, GLYPH_HEIGHT, |x, y| {
+        let (index, column) = (x / GLYPH_SPACING, x % GLYPH_SPACING);
+
+        return match glyphs[index as usize] {
+            Some(glyph) if column < GLYPH_WIDTH => {
+                glyph[y as usize].as_bytes()[column as usize] == b'#'
+            }
+            _ => false,
+        };
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_bitmap_is_correct() {
+        let bitmap = "\
+#..#..###
+#..#...#.
+####...#.
+#..#...#.
+#..#...#.
+#..#..###";
+
+        assert_eq!(Ok(String::from("HI")), read_bitmap(bitmap));
+    }
+
+    #[test]
+    fn read_bitmap_reports_unknown_glyph() {
+        let bitmap = "\
+.##..#...
+#..#.#...
+#..#.#...
+####.#...
+#..#.#...
+#..#.#...";
+
+        assert_eq!(
+            Err(OcrError::UnknownGlyph {
+                column: 5,
+                glyph: String::from("#...\n#...\n#...\n#...\n#...\n#..."),
+            }),
+            read_bitmap(bitmap)
+        );
+    }
+
+    #[test]
+    fn render_round_trips() {
+        let bitmap = render("HI ZK").unwrap();
+
+        assert_eq!((25, 6), (bitmap.width(), bitmap.height()));
+        assert_eq!(
+            Ok(String::from("HI ZK")),
+            read(25, 6, |x, y| bitmap.get(x, y))
+        );
+        assert_eq!(Err(OcrError::UnknownLetter { letter: 'q' }), render("q"));
+    }
+
+    #[test]
+    fn read_bitmap_rejects_bad_height() {
+        assert_eq!(
+            Err(OcrError::BadHeight { height: 2 }),
+            read_bitmap("####\n####")
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use crate::error::ParseError;
+use crate::solution::{Answer, Solution};
+use crate::{day1, day10, day2, day3, day4, day5, day6, day7, day8, day9};
+use std::any::Any;
+
+/// The parsed input of a day, with its type erased so that days can be stored together.
+pub type Parsed = Box<dyn Any>;
+
+/// A registered day. Wraps a `Solution` so it can be listed and called without knowing its
+/// input type.
+pub struct Day {
+    pub number: u8,
+    pub title: &'static str,
+    parse: fn(&str) -> Result<Parsed, ParseError>,
+    part1: fn(&Parsed) -> Result<Answer, String>,
+    part2: fn(&Parsed) -> Result<Answer, String>,
+}
+
+fn parse<S: Solution>(input: &str) -> Result<Parsed, ParseError>
+where
+    S::Input: 'static,
+{
+    return Ok(Box::new(S::parse(input)?));
+}
+
+fn part1<S: Solution>(input: &Parsed) -> Result<Answer, String>
+where
+    S::Input: 'static,
+{
+    return S::part1(
+        input
+            .downcast_ref()
+            .expect("input was parsed by another day"),
+    );
+}
+
+fn part2<S: Solution>(input: &Parsed) -> Result<Answer, String>
+where
+    S::Input: 'static,
+{
+    return S::part2(
+        input
+            .downcast_ref()
+            .expect("input was parsed by another day"),
+    );
+}
+
+impl Day {
+    pub fn of<S: Solution>() -> Day
+    where
+        S::Input: 'static,
+    {
+        return Day {
+            number: S::DAY,
+            title: S::TITLE,
+            parse: parse::<S>,
+            part1: part1::<S>,
+            part2: part2::<S>,
+        };
+    }
+
+    pub fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        return (self.parse)(input);
+    }
+
+    /// Solves `part` (1 or 2) of an input previously parsed by this day.
+    pub fn solve(&self, part: u8, input: &Parsed) -> Result<Answer, String> {
+        return match part {
+            1 => (self.part1)(input),
+            2 => (self.part2)(input),
+            _ => Err(format!("there is no part {}, only 1 and 2", part)),
+        };
+    }
+}
+
+/// Every implemented day, in calendar order.
+pub fn days() -> Vec<Day> {
+    return vec![
+        Day::of::<day1::Day1>(),
+        Day::of::<day2::Day2>(),
+        Day::of::<day3::Day3>(),
+        Day::of::<day4::Day4>(),
+        Day::of::<day5::Day5>(),
+        Day::of::<day6::Day6>(),
+        Day::of::<day7::Day7>(),
+        Day::of::<day8::Day8>(),
+        Day::of::<day9::Day9>(),
+        Day::of::<day10::Day10>(),
+    ];
+}
+
+pub fn find(number: u8) -> Option<Day> {
+    return days().into_iter().find(|day| day.number == number);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_are_registered_in_order() {
+        let numbers: Vec<u8> = days().iter().map(|day| day.number).collect();
+
+        assert_eq!(vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10], numbers);
+    }
+
+    #[test]
+    fn find_returns_registered_day() {
+        assert_eq!("Treetop Tree House", find(8).unwrap().title);
+        assert!(find(11).is_none());
+    }
+
+    #[test]
+    fn solve_is_correct() {
+        let day = find(6).unwrap();
+        let input = day.parse("mjqjpqmgbljsphdztnvjfqwrcgsmlb").unwrap();
+
+        assert_eq!(Ok(Answer::Number(7)), day.solve(1, &input));
+        assert_eq!(Ok(Answer::Number(19)), day.solve(2, &input));
+    }
+
+    #[test]
+    fn solve_rejects_unknown_part() {
+        let day = find(6).unwrap();
+        let input = day.parse("mjqjpqmgbljsphdztnvjfqwrcgsmlb").unwrap();
+
+        assert_eq!(
+            Err(String::from("there is no part 3, only 1 and 2")),
+            day.solve(3, &input)
+        );
+        assert!(day.solve(0, &input).is_err());
+    }
+}
@@ -0,0 +1,200 @@
+use crate::bench::nanoseconds;
+use crate::io_utils::Input;
+use crate::registry::Day;
+use crate::solution::Answer;
+use itertools::Itertools;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// The outcome of running one part of one day. When the input could not be read or parsed,
+/// or the part could not be solved, `answer` is missing and `error` says why.
+#[derive(Debug, Clone, Serialize)]
+pub struct PartReport {
+    pub day: u8,
+    pub title: &'static str,
+    pub part: u8,
+    pub input: String,
+    pub answer: Option<Answer>,
+    #[serde(rename = "type")]
+    pub kind: Option<&'static str>,
+    #[serde(rename = "parse_ns", serialize_with = "nanoseconds")]
+    pub parse_time: Duration,
+    #[serde(rename = "solve_ns", serialize_with = "nanoseconds")]
+    pub solve_time: Duration,
+    pub error: Option<String>,
+}
+
+impl PartReport {
+    pub fn passed(&self) -> bool {
+        return self.error.is_none();
+    }
+}
+
+/// Parses `filename` once and solves each of `parts` of `day` on it.
+pub fn run(day: &Day, parts: &[u8], filename: &str) -> Vec<PartReport> {
+    let report = |part: u8| PartReport {
+        day: day.number,
+        title: day.title,
+        part,
+        input: String::from(filename),
+        answer: None,
+        kind: None,
+        parse_time: Duration::ZERO,
+        solve_time: Duration::ZERO,
+        error: None,
+    };
+
+    let error = |message: String| {
+        return parts
+            .iter()
+            .map(|&part| PartReport {
+                error: Some(message.clone()),
+                ..report(part)
+            })
+            .collect();
+    };
+
+    let content = match Input::open(filename) {
+        Ok(content) => content,
+        Err(e) => return error(format!("could not read {}: {}", filename, e)),
+    };
+
+    let start = Instant::now();
+    let input = match day.parse(content.text()) {
+        Ok(input) => input,
+        Err(e) => return error(format!("could not parse {}: {}", filename, e)),
+    };
+    let parse_time = start.elapsed();
+
+    return parts
+        .iter()
+        .map(|&part| {
+            let start = Instant::now();
+            let answer = day.solve(part, &input);
+            let solve_time = start.elapsed();
+
+            return match answer {
+                Ok(answer) => PartReport {
+                    kind: Some(answer.kind()),
+                    answer: Some(answer),
+                    parse_time,
+                    solve_time,
+                    ..report(part)
+                },
+                Err(e) => PartReport {
+                    parse_time,
+                    solve_time,
+                    error: Some(e),
+                    ..report(part)
+                },
+            };
+        })
+        .collect();
+}
+
+/// Prints the answers for people: a header per day, followed by its parts. Errors go to stderr.
+pub fn print_report(reports: &[PartReport]) {
+    for (_, group) in &reports.iter().group_by(|x| (x.day, &x.input)) {
+        let group: Vec<&PartReport> = group.collect();
+        let first = group[0];
+
+        // The same error for every part is one the input or day had as a whole
+        if let Some(error) = &first.error {
+            if group.iter().all(|x| x.error == first.error) {
+                eprintln!("Day {}: {}", first.day, error);
+                continue;
+            }
+        }
+
+        println!(
+            "--- Day {}: {} --- (parsed in {:?})",
+            first.day, first.title, first.parse_time
+        );
+
+        for report in group {
+            if let Some(error) = &report.error {
+                eprintln!("Day {} part {}: {}", report.day, report.part, error);
+                continue;
+            }
+
+            let answer = report
+                .answer
+                .as_ref()
+                .map(|x| x.to_string())
+                .unwrap_or_default();
+
+            if answer.contains('\n') {
+                println!(
+                    "Day {} part {} ({:?}):\n{}",
+                    report.day,
+                    report.part,
+                    report.solve_time,
+                    answer.trim_end()
+                );
+            } else {
+                println!(
+                    "Day {} part {}: {} ({:?})",
+                    report.day, report.part, answer, report.solve_time
+                );
+            }
+        }
+    }
+}
+
+pub fn to_json(reports: &[PartReport]) -> String {
+    return serde_json::to_string_pretty(reports).expect("reports always serialize");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry;
+
+    #[test]
+    fn run_is_correct() {
+        let day = registry::find(1).unwrap();
+
+        let reports = run(&day, &[1, 2], "data/day1_example.txt");
+
+        assert_eq!(2, reports.len());
+        assert_eq!(Some(Answer::Number(24_000)), reports[0].answer);
+        assert_eq!(Some(Answer::Number(45_000)), reports[1].answer);
+        assert!(reports.iter().all(|x| x.passed()));
+    }
+
+    #[test]
+    fn run_reports_error_for_every_part() {
+        let day = registry::find(1).unwrap();
+
+        let reports = run(&day, &[1, 2], "data/missing.txt");
+
+        assert_eq!(2, reports.len());
+        assert!(reports.iter().all(|x| x.answer.is_none() && !x.passed()));
+    }
+
+    #[test]
+    fn run_reports_solve_error() {
+        let day = registry::find(10).unwrap();
+
+        let reports = run(&day, &[1], "data/day10_loop.txt");
+
+        assert_eq!(None, reports[0].answer);
+        assert!(reports[0].error.as_ref().unwrap().contains("loop forever"));
+        assert!(!reports[0].passed());
+    }
+
+    #[test]
+    fn to_json_is_correct() {
+        let day = registry::find(5).unwrap();
+        let reports = run(&day, &[1], "data/day5_example.txt");
+
+        let json: serde_json::Value = serde_json::from_str(&to_json(&reports)).unwrap();
+
+        assert_eq!(5, json[0]["day"]);
+        assert_eq!(1, json[0]["part"]);
+        assert_eq!("CMZ", json[0]["answer"]);
+        assert_eq!("text", json[0]["type"]);
+        assert!(json[0]["parse_ns"].is_u64());
+        assert!(json[0]["error"].is_null());
+    }
+}
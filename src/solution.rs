@@ -0,0 +1,85 @@
+use crate::error::ParseError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The answer to one part of a puzzle, regardless of the type the day computes it in.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Answer {
+    Number(i64),
+    Text(String),
+}
+
+impl Answer {
+    /// The name of the variant, as used in machine-readable output.
+    pub fn kind(&self) -> &'static str {
+        return match self {
+            Answer::Number(_) => "number",
+            Answer::Text(_) => "text",
+        };
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            Answer::Number(number) => write!(f, "{}", number),
+            Answer::Text(text) => write!(f, "{}", text),
+        };
+    }
+}
+
+impl From<u32> for Answer {
+    fn from(value: u32) -> Self {
+        return Answer::Number(value as i64);
+    }
+}
+
+impl From<i32> for Answer {
+    fn from(value: i32) -> Self {
+        return Answer::Number(value as i64);
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(value: i64) -> Self {
+        return Answer::Number(value);
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(value: usize) -> Self {
+        return Answer::Number(value as i64);
+    }
+}
+
+impl From<u64> for Answer {
+    fn from(value: u64) -> Self {
+        return Answer::Number(value as i64);
+    }
+}
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        return Answer::Text(value);
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(value: &str) -> Self {
+        return Answer::Text(String::from(value));
+    }
+}
+
+/// A single day of the calendar. The puzzle input is parsed once into `Input`, which both
+/// parts then solve. A part fails with a message when a valid input has no answer.
+pub trait Solution {
+    const DAY: u8;
+    const TITLE: &'static str;
+
+    type Input;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+    fn part1(input: &Self::Input) -> Result<Answer, String>;
+    fn part2(input: &Self::Input) -> Result<Answer, String>;
+}
@@ -0,0 +1,288 @@
+use crate::io_utils::Input;
+use crate::registry;
+use crate::solution::Answer;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// The expected answers of one input file.
+#[derive(Debug, Deserialize)]
+struct Parts {
+    part1: Option<Answer>,
+    part2: Option<Answer>,
+}
+
+/// An answers file maps `dayN` to the input file names of that day, and those to their answers.
+type AnswersFile = BTreeMap<String, BTreeMap<String, Parts>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expectation {
+    pub day: u8,
+    /// The name of the input file, relative to the directory of the answers file.
+    pub input: String,
+    pub part: u8,
+    pub answer: Answer,
+}
+
+#[derive(Debug)]
+pub enum AnswersError {
+    Io(io::Error),
+    Toml(toml::de::Error),
+    BadDay(String),
+}
+
+impl fmt::Display for AnswersError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            AnswersError::Io(error) => write!(f, "{}", error),
+            AnswersError::Toml(error) => write!(f, "{}", error),
+            AnswersError::BadDay(key) => write!(f, "{:?} is not a day, expected dayN", key),
+        };
+    }
+}
+
+pub fn parse_answers(text: &str) -> Result<Vec<Expectation>, AnswersError> {
+    let file: AnswersFile = toml::from_str(text).map_err(AnswersError::Toml)?;
+    let mut expectations = vec![];
+
+    for (key, inputs) in file {
+        let day: u8 = key
+            .strip_prefix("day")
+            .and_then(|x| x.parse().ok())
+            .ok_or_else(|| AnswersError::BadDay(key.clone()))?;
+
+        for (input, parts) in inputs {
+            for (part, answer) in [(1, parts.part1), (2, parts.part2)] {
+                if let Some(answer) = answer {
+                    expectations.push(Expectation {
+                        day,
+                        input: input.clone(),
+                        part,
+                        answer,
+                    });
+                }
+            }
+        }
+    }
+
+    expectations.sort_by(|a, b| (a.day, &a.input, a.part).cmp(&(b.day, &b.input, b.part)));
+
+    return Ok(expectations);
+}
+
+pub fn load_answers<P: AsRef<Path>>(path: P) -> Result<Vec<Expectation>, AnswersError> {
+    let text = fs::read_to_string(path).map_err(AnswersError::Io)?;
+
+    return parse_answers(&text);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Fail(Answer),
+    Error(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    pub expectation: Expectation,
+    pub status: Status,
+    pub parse_time: Duration,
+    pub solve_time: Duration,
+}
+
+impl Outcome {
+    pub fn passed(&self) -> bool {
+        return self.status == Status::Pass;
+    }
+}
+
+/// Runs every expectation, reading the inputs from `data_dir`. Each input is parsed once, no
+/// matter how many parts it has answers for.
+pub fn verify<P: AsRef<Path>>(expectations: &[Expectation], data_dir: P) -> Vec<Outcome> {
+    let mut outcomes = vec![];
+    let mut i = 0;
+
+    while i < expectations.len() {
+        let first = &expectations[i];
+        let group: Vec<&Expectation> = expectations[i..]
+            .iter()
+            .take_while(|x| x.day == first.day && x.input == first.input)
+            .collect();
+        i += group.len();
+
+        let error = |message: String| {
+            return group
+                .iter()
+                .map(|expectation| Outcome {
+                    expectation: (*expectation).clone(),
+                    status: Status::Error(message.clone()),
+                    parse_time: Duration::ZERO,
+                    solve_time: Duration::ZERO,
+                })
+                .collect::<Vec<Outcome>>();
+        };
+
+        let day = match registry::find(first.day) {
+            Some(day) => day,
+            None => {
+                outcomes.extend(error(format!("day {} is not implemented", first.day)));
+                continue;
+            }
+        };
+
+        let input = match Input::read_file(data_dir.as_ref().join(&first.input)) {
+            Ok(input) => input,
+            Err(e) => {
+                outcomes.extend(error(format!("could not read {}: {}", first.input, e)));
+                continue;
+            }
+        };
+
+        let start = Instant::now();
+        let parsed = match day.parse(input.text()) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                outcomes.extend(error(format!("could not parse {}: {}", first.input, e)));
+                continue;
+            }
+        };
+        let parse_time = start.elapsed();
+
+        for expectation in group {
+            let start = Instant::now();
+            let answer = day.solve(expectation.part, &parsed);
+            let solve_time = start.elapsed();
+
+            let status = match answer {
+                Ok(answer) if answer == expectation.answer => Status::Pass,
+                Ok(answer) => Status::Fail(answer),
+                Err(e) => Status::Error(e),
+            };
+
+            outcomes.push(Outcome {
+                expectation: expectation.clone(),
+                status,
+                parse_time,
+                solve_time,
+            });
+        }
+    }
+
+    return outcomes;
+}
+
+/// Prints a pass/fail table, followed by the details of every failure.
+pub fn print_report(outcomes: &[Outcome]) {
+    println!(
+        "{:>3}  {:>4}  {:<20}  {:<6}  {:>12}  {:>12}",
+        "Day", "Part", "Input", "Result", "Parse", "Solve"
+    );
+
+    for outcome in outcomes {
+        let result = match outcome.status {
+            Status::Pass => "PASS",
+            Status::Fail(_) => "FAIL",
+            Status::Error(_) => "ERROR",
+        };
+
+        println!(
+            "{:>3}  {:>4}  {:<20}  {:<6}  {:>12}  {:>12}",
+            outcome.expectation.day,
+            outcome.expectation.part,
+            outcome.expectation.input,
+            result,
+            format!("{:?}", outcome.parse_time),
+            format!("{:?}", outcome.solve_time)
+        );
+    }
+
+    for outcome in outcomes {
+        let expectation = &outcome.expectation;
+
+        match &outcome.status {
+            Status::Pass => {}
+            Status::Fail(answer) => println!(
+                "\nDay {} part {} ({}): expected\n{}\nbut got\n{}",
+                expectation.day, expectation.part, expectation.input, expectation.answer, answer
+            ),
+            Status::Error(message) => println!(
+                "\nDay {} part {} ({}): {}",
+                expectation.day, expectation.part, expectation.input, message
+            ),
+        }
+    }
+
+    let passed = outcomes.iter().filter(|x| x.passed()).count();
+    println!("\n{} of {} answers are correct", passed, outcomes.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_answers_is_correct() {
+        let expectations =
+            parse_answers("[day10.\"a.txt\"]\npart1 = 13\n\n[day2.\"b.txt\"]\npart2 = \"CMZ\"\n")
+                .unwrap();
+
+        assert_eq!(
+            vec![
+                Expectation {
+                    day: 2,
+                    input: String::from("b.txt"),
+                    part: 2,
+                    answer: Answer::Text(String::from("CMZ")),
+                },
+                Expectation {
+                    day: 10,
+                    input: String::from("a.txt"),
+                    part: 1,
+                    answer: Answer::Number(13),
+                },
+            ],
+            expectations
+        );
+    }
+
+    #[test]
+    fn parse_answers_rejects_bad_day() {
+        let result = parse_answers("[monday.\"a.txt\"]\npart1 = 13\n");
+
+        assert!(matches!(result, Err(AnswersError::BadDay(_))));
+    }
+
+    #[test]
+    fn verify_reports_wrong_answer() {
+        let expectations = parse_answers("[day1.\"day1_example.txt\"]\npart1 = 1\n").unwrap();
+
+        let outcomes = verify(&expectations, "data");
+
+        assert_eq!(Status::Fail(Answer::Number(24_000)), outcomes[0].status);
+    }
+
+    /// Checks every answer in data/answers.toml, so that answers can be added or corrected
+    /// without touching the tests.
+    #[test]
+    fn answers_are_correct() {
+        let expectations = load_answers("data/answers.toml").unwrap();
+
+        let failures: Vec<String> = verify(&expectations, "data")
+            .into_iter()
+            .filter(|x| !x.passed())
+            .map(|x| {
+                format!(
+                    "day {} part {} ({}): {:?}",
+                    x.expectation.day, x.expectation.part, x.expectation.input, x.status
+                )
+            })
+            .collect();
+
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+}
@@ -0,0 +1,214 @@
+#![allow(clippy::needless_return)]
+
+use aoc_2022::io_utils::Input;
+use aoc_2022::solution::Answer;
+use aoc_2022::{day10, day2, day4, day5, day7, day8, day9, registry};
+
+fn example(day: u8) -> String {
+    let input = Input::read_file(format!("./data/day{}_example.txt", day)).unwrap();
+
+    return String::from(input.text());
+}
+
+#[test]
+fn registered_examples_are_correct() {
+    let expected = [
+        (1, Answer::Number(24_000), Answer::Number(45_000)),
+        (2, Answer::Number(15), Answer::Number(12)),
+        (4, Answer::Number(2), Answer::Number(4)),
+        (7, Answer::Number(95_437), Answer::Number(24_933_642)),
+        (8, Answer::Number(21), Answer::Number(8)),
+    ];
+
+    for (number, part1, part2) in expected {
+        let day = registry::find(number).unwrap();
+        let input = day.parse(&example(number)).unwrap();
+
+        assert_eq!(Ok(part1), day.solve(1, &input), "day {} part 1", number);
+        assert_eq!(Ok(part2), day.solve(2, &input), "day {} part 2", number);
+    }
+}
+
+#[test]
+fn shapes_are_scored() {
+    let round = day2::Part1Instruction::new(day2::Shape::Rock, day2::Shape::Paper);
+
+    assert_eq!(8, round.score());
+    assert_eq!(day2::Shape::Paper, day2::Shape::Rock.loses_to());
+}
+
+#[test]
+fn ranges_are_compared() {
+    let outer = day4::Range::new(2, 8);
+    let inner = day4::Range::new(3, 7);
+
+    assert!(outer.contains(&inner));
+    assert!(!inner.contains(&outer));
+    assert!(inner.overlaps(&day4::Range::new(7, 9)));
+}
+
+#[test]
+fn cargo_bay_follows_procedure() {
+    let procedure = day5::parse(&example(5)).unwrap();
+    let mut cargo_bay = procedure.cargo_bay().clone();
+
+    assert_eq!("NDP", cargo_bay.top_crates());
+
+    cargo_bay
+        .handle_move(&day5::CargoMove::new(1, 2, 1))
+        .unwrap();
+
+    assert_eq!(vec!['Z', 'N', 'D'], cargo_bay.stacks()[0]);
+    assert_eq!(4, procedure.cargo_moves().len());
+}
+
+#[test]
+fn file_system_sizes_are_correct() {
+    let file_system = day7::parse(&example(7)).unwrap();
+
+    assert_eq!(48_381_165, file_system.total_size());
+    assert_eq!(4, file_system.sizes().len());
+}
+
+#[test]
+fn grid_is_queried() {
+    let grid = day8::parse(&example(8)).unwrap();
+    let tree = day8::Vec2 { x: 2, y: 3 };
+
+    assert_eq!((5, 5), (grid.width(), grid.height()));
+    assert_eq!(Some(5), grid.get(&tree));
+    assert_eq!(8, grid.scenic_score(&tree));
+    assert!(grid.is_visible(&tree));
+}
+
+#[test]
+fn vectors_are_added() {
+    let a = day9::Vec2::new(1, -2);
+
+    assert_eq!(day9::Vec2::new(4, 0), a.plus(&day9::Vec2::new(3, 2)));
+    assert_eq!(day9::Vec2::new(0, 0), a.minus(&a));
+}
+
+#[test]
+fn rope_visits_cells() {
+    let mut rope = day9::Rope::new(2);
+
+    for _ in 0..3 {
+        rope.step(&day9::Vec2::new(1, 0));
+    }
+
+    assert_eq!(day9::Vec2::new(2, 0), rope.tail());
+    assert_eq!(3, rope.visited().len());
+}
+
+#[test]
+fn rope_follows_rule() {
+    let mut rope = day9::Rope::with_rule(2, day9::Slack { max_length: 2 });
+
+    for _ in 0..4 {
+        rope.step(&day9::Vec2::new(1, 1));
+    }
+
+    assert_eq!(day9::Vec2::new(2, 2), rope.tail());
+    assert_eq!(3, rope.visited().len());
+}
+
+#[test]
+fn cpu_runs_instructions() {
+    let program = day10::assemble("noop\naddx 3 ; right\naddx -5").unwrap();
+    let mut xs = vec![];
+    let mut sampler = day10::SignalSampler::new(&[4]);
+
+    day10::Cpu::new()
+        .run(
+            &program.ops,
+            &mut [
+                &mut |cpu: &day10::Cpu| xs.push(cpu.registers.x),
+                &mut sampler,
+            ],
+        )
+        .unwrap();
+
+    assert_eq!(vec![1, 1, 1, 4, 4], xs);
+    assert_eq!(16, sampler.total());
+}
+
+#[test]
+fn long_program_without_jumps_runs_past_cycle_limit() {
+    let source = "addx 1\naddx -1\n".repeat(300_000);
+    let day = registry::find(10).unwrap();
+    let input = day.parse(&source).unwrap();
+
+    assert!(day.solve(1, &input).is_ok());
+    assert_eq!(
+        Ok(1_200_000),
+        day10::cycle_count(
+            &day10::parse(&source).unwrap(),
+            day10::ScreenConfig::default()
+        )
+    );
+}
+
+#[test]
+fn cpu_streams_generated_program() {
+    // Far too many instructions to keep, generated as they run
+    let ops = (0..1_000_000).map(|i| match i % 3 {
+        0 => day10::Op::Noop,
+        1 => day10::Op::AddX(1),
+        _ => day10::Op::AddX(-1),
+    });
+    let mut sampler = day10::SignalSampler::every(1, 5);
+    let mut cpu = day10::Cpu::new();
+
+    cpu.run_stream(ops, &mut [&mut sampler]).unwrap();
+
+    assert_eq!(1_666_666, cpu.cycle);
+    assert_eq!(1, cpu.registers.x);
+    assert_eq!(333_334, sampler.samples());
+}
+
+#[test]
+fn streamed_example_matches_part1() {
+    let text = example(10);
+    let program = day10::parse(&text).unwrap();
+    let mut sampler = day10::SignalSampler::every(20, 40).until(220);
+    let mut cpu = day10::Cpu::new();
+
+    itertools::process_results(day10::stream_ops(text.lines()), |ops| {
+        cpu.run_stream(ops, &mut [&mut sampler])
+    })
+    .unwrap()
+    .unwrap();
+
+    assert_eq!(Ok(sampler.total()), day10::part1(&program));
+}
+
+#[test]
+fn screen_draws_sprite() {
+    let program = day10::Program::from_ops(vec![day10::Op::Noop]);
+    let mut screen = day10::CRTScreen::new(4, 1);
+
+    day10::Cpu::new()
+        .run(&program.ops, &mut [&mut screen])
+        .unwrap();
+
+    assert_eq!("#...\n", screen.to_string());
+}
+
+#[test]
+fn program_round_trips_through_text() {
+    let program = day10::assemble("loop:  addx 2\n; nothing to see\nnoop").unwrap();
+
+    assert_eq!("loop:\naddx 2\nnoop\n", program.to_string());
+}
+
+#[test]
+fn generated_program_draws_text() {
+    let config = day10::ScreenConfig::default();
+    let program = day10::generate_text("BEEF", config).unwrap();
+
+    let day = registry::find(10).unwrap();
+    let input = day.parse(&day10::disassemble(&program)).unwrap();
+
+    assert_eq!(Ok(Answer::Text(String::from("BEEF"))), day.solve(2, &input));
+}